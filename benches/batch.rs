@@ -0,0 +1,79 @@
+use std::hash::BuildHasher;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::Rng;
+
+use gxhash::*;
+
+// Intel Xeon (VM) | rustc 1.95.0 | 1024 keys, batches of 8
+// - bytes 8-16:    gxhash64 1.26 µs / gxhash64_batch 1.30 µs
+// - bytes 8-64:    gxhash64 1.97 µs / gxhash64_batch 2.03 µs
+// - bytes 64-256:  gxhash64 4.34 µs / gxhash64_batch 4.26 µs
+// - u64:           hash_one 0.70 µs / hash_batch 0.52 µs
+// - string 8-64:   hash_one 3.02 µs / hash_batch 2.24 µs
+// Consecutive gxhash64 calls are already overlapped by the out-of-order engine of this CPU.
+
+const KEYS: usize = 1024;
+
+// Keys of random lengths, so that the length branches of the compression are not predictable
+fn random_keys(min_len: usize, max_len: usize) -> Vec<Vec<u8>> {
+    let mut rng = rand::thread_rng();
+    (0..KEYS).map(|_| {
+        let mut key = vec![0u8; rng.gen_range(min_len..=max_len)];
+        rng.fill(key.as_mut_slice());
+        key
+    }).collect()
+}
+
+fn benchmark_batch(c: &mut Criterion) {
+    let mut hashes = vec![0u64; KEYS];
+
+    for (name, min_len, max_len) in [("8-16", 8, 16), ("8-64", 8, 64), ("64-256", 64, 256)] {
+        let keys = random_keys(min_len, max_len);
+        let keys: Vec<&[u8]> = keys.iter().map(|key| key.as_slice()).collect();
+
+        let mut group = c.benchmark_group(format!("batch/bytes {name}"));
+        group.throughput(Throughput::Elements(KEYS as u64));
+        group.bench_function("gxhash64", |b| b.iter(|| {
+            for (key, hash) in black_box(&keys).iter().zip(hashes.iter_mut()) {
+                *hash = gxhash64(key, 42);
+            }
+        }));
+        group.bench_function("gxhash64_batch", |b| b.iter(|| {
+            gxhash64_batch(black_box(&keys), 42, &mut hashes);
+        }));
+        group.finish();
+    }
+
+    let build_hasher = GxBuildHasher::default();
+
+    let keys: Vec<u64> = (0..KEYS).map(|_| rand::random()).collect();
+    let mut group = c.benchmark_group("batch/u64");
+    group.throughput(Throughput::Elements(KEYS as u64));
+    group.bench_function("hash_one", |b| b.iter(|| {
+        for (key, hash) in black_box(&keys).iter().zip(hashes.iter_mut()) {
+            *hash = build_hasher.hash_one(key);
+        }
+    }));
+    group.bench_function("hash_batch", |b| b.iter(|| {
+        build_hasher.hash_batch(black_box(&keys), &mut hashes);
+    }));
+    group.finish();
+
+    let keys: Vec<String> = random_keys(8, 64).into_iter().map(|key| key.iter().map(|b| (b'a' + b % 26) as char).collect()).collect();
+    let mut group = c.benchmark_group("batch/string 8-64");
+    group.throughput(Throughput::Elements(KEYS as u64));
+    group.bench_function("hash_one", |b| b.iter(|| {
+        for (key, hash) in black_box(&keys).iter().zip(hashes.iter_mut()) {
+            *hash = build_hasher.hash_one(key);
+        }
+    }));
+    group.bench_function("hash_batch", |b| b.iter(|| {
+        build_hasher.hash_batch(black_box(&keys), &mut hashes);
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_batch);
+criterion_main!(benches);
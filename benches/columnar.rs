@@ -0,0 +1,69 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::Rng;
+
+use gxhash::columnar::*;
+use gxhash::*;
+
+// Intel Xeon (VM) | rustc 1.95.0 | 4096 rows
+// - u64:        gxhash64 56.4 µs / hash_u64 2.25 µs / hash_u64_column 2.08 µs
+// - (u64, u32): combine 7.21 µs / combine_nullable 5.01 µs
+// - f64:        gxhash64 51.8 µs / hash_f64_column 4.15 µs
+// Hashing integers with hash_u64 in a loop is already pipelined by the out-of-order engine of this CPU.
+
+const ROWS: usize = 4096;
+
+fn benchmark_columnar(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let mut hashes = vec![0u64; ROWS];
+
+    let column: Vec<u64> = (0..ROWS).map(|_| rng.gen()).collect();
+    let mut group = c.benchmark_group("columnar/u64");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.bench_function("gxhash64", |b| b.iter(|| {
+        for (value, hash) in black_box(&column).iter().zip(hashes.iter_mut()) {
+            *hash = gxhash64(&value.to_ne_bytes(), 42);
+        }
+    }));
+    group.bench_function("hash_u64", |b| b.iter(|| {
+        for (value, hash) in black_box(&column).iter().zip(hashes.iter_mut()) {
+            *hash = hash_u64(*value, 42);
+        }
+    }));
+    group.bench_function("hash_u64_column", |b| b.iter(|| {
+        hash_u64_column(black_box(&column), 42, &mut hashes);
+    }));
+    group.finish();
+
+    // Second column of a multi-column key, with about 1 null in 8
+    let second: Vec<u32> = (0..ROWS).map(|_| rng.gen()).collect();
+    let validity: Vec<u8> = (0..ROWS / 8).map(|_| rng.gen::<u8>() | rng.gen::<u8>() | rng.gen::<u8>()).collect();
+    let mut group = c.benchmark_group("columnar/(u64, u32)");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.bench_function("combine", |b| b.iter(|| {
+        hash_u64_column(black_box(&column), 42, &mut hashes);
+        combine_u32_column(black_box(&second), 42, &mut hashes);
+    }));
+    group.bench_function("combine_nullable", |b| b.iter(|| {
+        hash_u64_column(black_box(&column), 42, &mut hashes);
+        combine_u32_column_nullable(black_box(&second), black_box(&validity), 42, &mut hashes);
+    }));
+    group.finish();
+
+    let column: Vec<f64> = (0..ROWS).map(|_| rng.gen()).collect();
+    let mut group = c.benchmark_group("columnar/f64");
+    group.throughput(Throughput::Elements(ROWS as u64));
+    group.bench_function("gxhash64", |b| b.iter(|| {
+        for (value, hash) in black_box(&column).iter().zip(hashes.iter_mut()) {
+            *hash = gxhash64(&value.to_ne_bytes(), 42);
+        }
+    }));
+    group.bench_function("hash_f64_column", |b| b.iter(|| {
+        hash_f64_column(black_box(&column), 42, &mut hashes);
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_columnar);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::Rng;
+
+use gxhash::*;
+
+macro_rules! bench_sizes {
+    ($group:ident, $($n:literal),+) => {
+        $(
+            let mut input = [0u8; $n];
+            rand::thread_rng().fill(input.as_mut_slice());
+            $group.bench_with_input(BenchmarkId::new("gxhash64", $n), &input, |bencher, input| {
+                bencher.iter(|| gxhash64(black_box(input.as_slice()), 42))
+            });
+            $group.bench_with_input(BenchmarkId::new("gxhash64_fixed", $n), &input, |bencher, input| {
+                bencher.iter(|| gxhash64_fixed(black_box(input), 42))
+            });
+        )+
+    };
+}
+
+fn benchmark_fixed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fixed");
+    bench_sizes!(group,
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27,
+        28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52,
+        53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77,
+        78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101,
+        102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121,
+        122, 123, 124, 125, 126, 127, 128
+    );
+    group.finish();
+}
+
+criterion_group!(benches, benchmark_fixed);
+criterion_main!(benches);
@@ -7,10 +7,22 @@ use std::hash::Hash;
 use std::collections::HashSet;
 use std::hash::{BuildHasherDefault, BuildHasher};
 
+// Composite key with several small fields, each hashed with a separate `write_*` call
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+struct Composite {
+    id: u32,
+    kind: u16,
+    flags: u8,
+    timestamp: u64,
+    active: bool,
+}
+
 fn hashset_contains(c: &mut Criterion) {
     benchmark(c, "u32", 42u32);
     benchmark(c, "u64", 42u64);
     benchmark(c, "u128", 42u128);
+    benchmark(c, "tuple", (42u32, 7u16, 3u8, 1234u64));
+    benchmark(c, "struct", Composite { id: 42, kind: 7, flags: 3, timestamp: 1234, active: true });
     benchmark(c, "small string", "gxhash".to_owned());
     benchmark(c, "medium string", "https://github.com/ogxd/gxhash".to_owned());
     benchmark(c, "large string","Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla pariatur. Excepteur sint occaecat cupidatat non proident, sunt in culpa qui officia deserunt mollit anim id est laborum.".to_owned());
@@ -14,6 +14,12 @@ fn main() {
     // Hash functions that don't pass the quality tests ❌
     bench_hasher_quality::<fnv::FnvBuildHasher>("FNV-1a");
     bench_hasher_quality::<foldhash::quality::RandomState>("FoldHash");
+
+    // Integer keys (FxHash and FoldHash fail avalanche)
+    bench_int_hasher_quality::<gxhash::GxIntBuildHasher>("GxHash (integers)");
+    bench_int_hasher_quality::<gxhash::GxBuildHasher>("GxHash");
+    bench_int_hasher_quality::<BuildHasherDefault<rustc_hash::FxHasher>>("FxHash (rustc_hash)");
+    bench_int_hasher_quality::<foldhash::fast::RandomState>("FoldHash (fast)");
 }
 
 macro_rules! check {
@@ -84,6 +90,138 @@ fn bench_hasher_quality<B>(name: &str)
     check!(hasher_collisions_powerset::<B, u32>(&[0, 1, 2, 4, 8, 16, 32, 64, 128, 256, 512, 1024, 2048, 4096, 8192, 16384]));
 }
 
+fn bench_int_hasher_quality<B>(name: &str)
+    where B : BuildHasher + Default
+{
+    println!("Bench integers {}", name);
+
+    check!(avalanche_int::<B, u32>());
+    check!(avalanche_int::<B, u64>());
+    check!(avalanche_int::<B, u128>());
+
+    check!(distribution_values_int::<B, u32>(128 * 128));
+    check!(distribution_values_int::<B, u64>(128 * 128));
+    check!(distribution_values_int::<B, u128>(128 * 128));
+
+    check!(distribution_bits_int::<B, u32>());
+    check!(distribution_bits_int::<B, u64>());
+    check!(distribution_bits_int::<B, u128>());
+
+    check!(collisions_sequential_int::<B, u32>(0, 1_000_000));
+    check!(collisions_sequential_int::<B, u64>(0, 1_000_000));
+    check!(collisions_sequential_int::<B, u64>(32, 1_000_000));
+    check!(collisions_sequential_int::<B, u128>(64, 1_000_000));
+}
+
+trait Int: Hash + Copy {
+    const BITS: u32;
+    fn from_u128(value: u128) -> Self;
+    fn flip(self, bit: u32) -> Self;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),+) => {
+        $(
+            impl Int for $t {
+                const BITS: u32 = <$t>::BITS;
+                fn from_u128(value: u128) -> Self { value as $t }
+                fn flip(self, bit: u32) -> Self { self ^ (1 << bit) }
+            }
+        )+
+    };
+}
+
+impl_int!(u32, u64, u128);
+
+fn avalanche_int<B, T: Int>() -> f64
+    where B : BuildHasher + Default
+{
+    const AVALANCHE_ITERATIONS: usize = 10000;
+    const AVG_ITERATIONS: usize = 10;
+
+    let mut sum: f64 = 0f64;
+    for _ in 0..AVG_ITERATIONS {
+        let build_hasher = B::default();
+        let mut rng = rand::thread_rng();
+        let mut scores_sum = 0f64;
+        for _ in 0..AVALANCHE_ITERATIONS {
+            let value = T::from_u128(rng.gen());
+            let v1 = build_hasher.hash_one(value);
+            for i in 0..T::BITS {
+                let v2 = build_hasher.hash_one(black_box(value.flip(i)));
+                scores_sum += (v1 ^ v2).count_ones() as f64 / 64f64;
+            }
+        }
+        let count = AVALANCHE_ITERATIONS * T::BITS as usize;
+        sum += (1.0 - 2.0 * (scores_sum / count as f64)).abs();
+    }
+
+    let score = sum / AVG_ITERATIONS as f64;
+    round_to_decimal(score, (AVALANCHE_ITERATIONS as f64).log10() as usize)
+}
+
+fn distribution_bits_int<B, T: Int>() -> f64
+    where B : BuildHasher + Default
+{
+    const DISTRIBUTION_ITERATIONS: usize = 10000;
+    const AVG_ITERATIONS: usize = 100;
+
+    let mut sum: f64 = 0f64;
+    for _ in 0..AVG_ITERATIONS {
+        let build_hasher = B::default();
+        let mut rng = rand::thread_rng();
+        let mut bit_buckets = vec![0f64; 64];
+        for _ in 0..DISTRIBUTION_ITERATIONS {
+            let hash = build_hasher.hash_one(T::from_u128(rng.gen()));
+            for (k, bucket) in bit_buckets.iter_mut().enumerate() {
+                *bucket += ((hash >> k) & 1) as f64;
+            }
+        }
+        bit_buckets = bit_buckets.iter().map(|x| x / DISTRIBUTION_ITERATIONS as f64).collect();
+        // The worst possible variance for a set of values between 0 and 1 is 0.25
+        sum += variance_to_mean(&bit_buckets, 0.5) / 0.25;
+    }
+
+    let score = sum / AVG_ITERATIONS as f64;
+    round_to_decimal(score, (DISTRIBUTION_ITERATIONS as f64).log10() as usize)
+}
+
+fn distribution_values_int<B, T: Int>(buckets_count: usize) -> f64
+    where B : BuildHasher + Default
+{
+    const DISTRIBUTION_ITERATIONS: usize = 100000;
+    const AVG_ITERATIONS: usize = 100;
+
+    let mut sum: f64 = 0f64;
+    for _ in 0..AVG_ITERATIONS {
+        let build_hasher = B::default();
+        let mut rng = rand::thread_rng();
+        let mut buckets = vec![0f64; buckets_count];
+        for _ in 0..DISTRIBUTION_ITERATIONS {
+            let hash = build_hasher.hash_one(T::from_u128(rng.gen()));
+            let index = (buckets_count as f64 * (hash as f64 / u64::MAX as f64)).floor() as usize;
+            buckets[index.min(buckets_count - 1)] += 1f64;
+        }
+        buckets = buckets.iter().map(|x| x / DISTRIBUTION_ITERATIONS as f64).collect();
+        // The worst possible variance for these buckets is 1 / buckets_count
+        sum += variance(&buckets) * buckets_count as f64;
+    }
+
+    let score = sum / AVG_ITERATIONS as f64;
+    round_to_decimal(score, (DISTRIBUTION_ITERATIONS as f64).log10() as usize)
+}
+
+// Collisions for sequential integers, shifted to the left to only vary the higher bits
+fn collisions_sequential_int<B, T: Int>(shift: u32, count: u128) -> f64
+    where B : BuildHasher + Default
+{
+    let build_hasher = B::default();
+    let set: HashSet<u64> = (0..count).map(|i| build_hasher.hash_one(T::from_u128(i << shift))).collect();
+
+    // Collision rate
+    (count as usize - set.len()) as f64 / count as f64
+}
+
 fn hasher_collisions_permute<B, D>(data: &[impl Hash]) -> f64
     where B : BuildHasher + Default
 {
@@ -1,3 +1,20 @@
+use std::env;
+use std::process::Command;
+
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(stable_vaes)");
+
+    // VAES intrinsics are stable since Rust 1.89, before which the `hybrid` feature requires nightly
+    if matches!(rustc_minor_version(), Some(minor) if minor >= 89) {
+        println!("cargo:rustc-cfg=stable_vaes");
+    }
+}
 
-}
\ No newline at end of file
+fn rustc_minor_version() -> Option<u32> {
+    let rustc = env::var_os("RUSTC")?;
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    // Format is "rustc 1.89.0 (...)"
+    version.split_whitespace().nth(1)?.split('.').nth(1)?.parse().ok()
+}
@@ -0,0 +1,97 @@
+use crate::gxhash::platform::*;
+use crate::gxhash::*;
+
+/// Number of hashes computed together by the batch functions.
+///
+/// The AES rounds of a single small hash form a dependency chain, each round waiting on the previous one.
+/// Finalizing several independent hashes together gives the CPU as many independent chains to pipeline.
+pub(crate) const LANES: usize = 8;
+
+/// Hashes many inputs to u64s. Each hash is the same as [`gxhash64`] on the corresponding input.
+///
+/// Inputs are hashed by groups of 8, whose finalizations are independent of each other so that the CPU can
+/// pipeline their AES rounds. The gain over calling [`gxhash64`] in a loop depends on how much of this overlap
+/// the CPU already finds on its own (see `benches/batch.rs`).
+///
+/// # Panics
+/// Panics if `inputs` and `output` don't have the same length.
+///
+/// # Example
+///
+/// ```
+/// let inputs: [&[u8]; 3] = [b"hello", b"world", b"!"];
+/// let mut hashes = [0u64; 3];
+/// gxhash::gxhash64_batch(&inputs, 1234, &mut hashes);
+/// assert_eq!(gxhash::gxhash64(b"world", 1234), hashes[1]);
+/// ```
+#[inline]
+pub fn gxhash64_batch(inputs: &[&[u8]], seed: i64, output: &mut [u64]) {
+    assert_eq!(inputs.len(), output.len(), "There must be as many outputs as inputs");
+    dispatch!(B => unsafe {
+        let seed = create_seed(seed);
+        let mut input_groups = inputs.chunks_exact(LANES);
+        let mut output_groups = output.chunks_exact_mut(LANES);
+        for (inputs, output) in (&mut input_groups).zip(&mut output_groups) {
+            let mut states = [create_empty(); LANES];
+            for (state, input) in states.iter_mut().zip(inputs) {
+                *state = B::aes_encrypt(compress_all_with::<B>(input, &KEYS, Profile::Default), seed);
+            }
+            store_u64(finalize_lanes::<B>(states, Profile::Default), output);
+        }
+        for (input, hash) in input_groups.remainder().iter().zip(output_groups.into_remainder()) {
+            let p = &gxhash::<B>(input, seed) as *const State as *const u64;
+            *hash = u64::from_le(*p);
+        }
+    })
+}
+
+/// Same as [`finalize_with`] with the default round keys on each state. Lanes don't depend on each other,
+/// so that the CPU can pipeline their rounds.
+#[inline(always)]
+pub(crate) unsafe fn finalize_lanes<B: Aes>(states: [State; LANES], profile: Profile) -> [State; LANES] {
+    states.map(|state| finalize_with::<B>(state, &KEYS, profile))
+}
+
+/// Stores the first 64 bits of each state.
+#[inline(always)]
+pub(crate) unsafe fn store_u64(states: [State; LANES], output: &mut [u64]) {
+    for (state, hash) in states.iter().zip(output) {
+        let p = state as *const State as *const u64;
+        *hash = u64::from_le(*p);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn batch_hashes_are_single_hashes() {
+        let mut rng = rand::thread_rng();
+        let mut bytes = [0u8; 4096];
+        rng.fill(bytes.as_mut_slice());
+
+        // Every group size and remainder, with random lengths across the length branches of the compression
+        for _ in 0..100 {
+            for count in 0..=2 * LANES + 1 {
+                let inputs: Vec<&[u8]> = (0..count).map(|_| {
+                    let start = rng.gen_range(0..bytes.len() - 300);
+                    &bytes[start..start + rng.gen_range(0..300)]
+                }).collect();
+                let mut hashes = vec![0u64; count];
+                gxhash64_batch(&inputs, 42, &mut hashes);
+                for (input, hash) in inputs.iter().zip(&hashes) {
+                    assert_eq!(gxhash64(input, 42), *hash, "count {count}, len {}", input.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "There must be as many outputs as inputs")]
+    fn output_of_other_length_panics() {
+        gxhash64_batch(&[b"hello"], 42, &mut [0u64; 2]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Hashing of columns of fixed-width values, for hash joins and group-bys in columnar query engines.
+//!
+//! Values are hashed with the same AES rounds as [`hash_u64`](crate::hash_u64) and its siblings, by groups of 8
+//! whose rounds are interleaved, without the length handling of [`gxhash64`](crate::gxhash64).
+//! The hash of a value in a column is the same as [`GxIntBuildHasher::hash_one`](core::hash::BuildHasher::hash_one)
+//! on that value with the same seed.
+//!
+//! # Multi-column keys
+//! The `combine_*_column` functions combine the values of a column into an existing column of hashes, so that keys
+//! made of several columns are hashed one column at a time. Each hash becomes the same as
+//! [`GxIntBuildHasher::hash_one`](core::hash::BuildHasher::hash_one) on the `(previous_hash, value)` tuple.
+//! The combination is order-dependent: hashing `a` then combining `b` is not the same as hashing `b` then combining `a`.
+//!
+//! # Nulls
+//! The `*_nullable` functions take a validity bitmap in the Apache Arrow layout: value `i` is valid if bit `i % 8`
+//! (least significant bit first) of byte `i / 8` is set, and null otherwise. Valid values hash the same as in a
+//! column without a bitmap, so that a nullable column can be joined with a column that is not. Nulls are hashed as
+//! if nothing was written to the hasher: all nulls have the same hash, and a null combined into a hash is not the
+//! same as leaving the hash untouched, so that `(a, null, b)` and `(a, b, null)` don't collide.
+//!
+//! # Panics
+//! All functions panic if `values` and `hashes` don't have the same length, and the `*_nullable` functions panic if
+//! the bitmap is too short to have a bit per value.
+//!
+//! # Example
+//!
+//! ```
+//! use std::hash::BuildHasher;
+//! use gxhash::columnar::*;
+//!
+//! // Key made of a (user_id, country) pair, where the country may be null
+//! let user_ids = [1u64, 2, 3];
+//! let countries = [33u32, 0, 44];
+//! let validity = [0b101u8];
+//!
+//! let mut hashes = [0u64; 3];
+//! hash_u64_column(&user_ids, 1234, &mut hashes);
+//! combine_u32_column_nullable(&countries, &validity, 1234, &mut hashes);
+//!
+//! let build_hasher = gxhash::GxIntBuildHasher::with_seed(1234);
+//! assert_eq!(build_hasher.hash_one((build_hasher.hash_one(3u64), 44u32)), hashes[2]);
+//! ```
+
+use crate::batch::LANES;
+use crate::gxhash::platform::*;
+use crate::integer::*;
+
+macro_rules! column {
+    ($type:ty, $hash:ident, $combine:ident, $hash_nullable:ident, $combine_nullable:ident, $same_as:literal, $absorb:expr) => {
+        #[doc = concat!("Hashes a column of `", stringify!($type), "` values into `hashes`. ", $same_as)]
+        #[inline]
+        pub fn $hash(values: &[$type], seed: i64, hashes: &mut [u64]) {
+            dispatch!(B => unsafe { hash_column::<B, _, _>(values, None, seed, false, hashes, $absorb) })
+        }
+
+        #[doc = concat!("Combines a column of `", stringify!($type), "` values into existing `hashes`, for multi-column keys. ")]
+        #[doc = "See the [module documentation](self) for how hashes are combined."]
+        #[inline]
+        pub fn $combine(values: &[$type], seed: i64, hashes: &mut [u64]) {
+            dispatch!(B => unsafe { hash_column::<B, _, _>(values, None, seed, true, hashes, $absorb) })
+        }
+
+        #[doc = concat!("Same as [`", stringify!($hash), "`], with nulls given by a validity bitmap. ")]
+        #[doc = "See the [module documentation](self) for the bitmap layout and how nulls are hashed."]
+        #[inline]
+        pub fn $hash_nullable(values: &[$type], validity: &[u8], seed: i64, hashes: &mut [u64]) {
+            dispatch!(B => unsafe { hash_column::<B, _, _>(values, Some(validity), seed, false, hashes, $absorb) })
+        }
+
+        #[doc = concat!("Same as [`", stringify!($combine), "`], with nulls given by a validity bitmap. ")]
+        #[doc = "See the [module documentation](self) for the bitmap layout and how nulls are hashed."]
+        #[inline]
+        pub fn $combine_nullable(values: &[$type], validity: &[u8], seed: i64, hashes: &mut [u64]) {
+            dispatch!(B => unsafe { hash_column::<B, _, _>(values, Some(validity), seed, true, hashes, $absorb) })
+        }
+    }
+}
+
+column!(u32, hash_u32_column, combine_u32_column, hash_u32_column_nullable, combine_u32_column_nullable,
+    "Each hash is the same as [`hash_u32`](crate::hash_u32) on the value with the same seed.",
+    |state, value| absorb::<B>(state, load_u32(value)));
+
+column!(u64, hash_u64_column, combine_u64_column, hash_u64_column_nullable, combine_u64_column_nullable,
+    "Each hash is the same as [`hash_u64`](crate::hash_u64) on the value with the same seed.",
+    |state, value| absorb::<B>(state, load_u64(value)));
+
+column!(i64, hash_i64_column, combine_i64_column, hash_i64_column_nullable, combine_i64_column_nullable,
+    "Each hash is the same as [`hash_u64`](crate::hash_u64) on the value cast to an `u64` with the same seed.",
+    |state, value| absorb::<B>(state, load_i64(value)));
+
+column!(u128, hash_u128_column, combine_u128_column, hash_u128_column_nullable, combine_u128_column_nullable,
+    "Each hash is the same as [`hash_u128`](crate::hash_u128) on the value with the same seed.",
+    |state, value| absorb_wide::<B>(state, load_u128(value)));
+
+column!(f64, hash_f64_column, combine_f64_column, hash_f64_column_nullable, combine_f64_column_nullable,
+    "Values that compare equal hash the same (`-0.0` hashes as `0.0`) and all NaNs have the same hash, as grouping \
+    expects. Other values hash the same as [`hash_u64`](crate::hash_u64) on their bits with the same seed.",
+    |state, value| absorb::<B>(state, load_u64(canonical_bits(value))));
+
+// Adding a positive zero turns a negative zero into a positive zero, and leaves other values untouched
+#[inline(always)]
+fn canonical_bits(value: f64) -> u64 {
+    if value.is_nan() { f64::NAN.to_bits() } else { (value + 0.0).to_bits() }
+}
+
+// usize::div_ceil requires Rust 1.73
+#[allow(clippy::manual_div_ceil)]
+#[inline(always)]
+unsafe fn hash_column<B: Aes, T: Copy, F>(values: &[T], validity: Option<&[u8]>, seed: i64, combine: bool, hashes: &mut [u64], absorb_value: F)
+    where F: Fn(State, T) -> State
+{
+    assert_eq!(values.len(), hashes.len(), "There must be as many hashes as values");
+    if let Some(validity) = validity {
+        assert!(validity.len() >= (values.len() + 7) / 8, "The validity bitmap must have a bit per value");
+    }
+
+    let seed = create_seed(seed);
+    // Previous hashes are absorbed like integers, as GxIntHasher would for the first field of a tuple
+    let start = |hash: u64| if combine { absorb::<B>(seed, load_u64(hash)) } else { seed };
+
+    let mut value_groups = values.chunks_exact(LANES);
+    let mut hash_groups = hashes.chunks_exact_mut(LANES);
+    // Groups of 8 values have their validity in a single byte of the bitmap
+    for (group, (values, hashes)) in (&mut value_groups).zip(&mut hash_groups).enumerate() {
+        let valid = validity.map_or(u8::MAX, |validity| validity[group]);
+        let states: [State; LANES] = core::array::from_fn(|lane| start(hashes[lane]));
+        let states: [State; LANES] = core::array::from_fn(|lane| {
+            if (valid >> lane) & 1 != 0 { absorb_value(states[lane], values[lane]) } else { states[lane] }
+        });
+        for (hash, state) in hashes.iter_mut().zip(states) {
+            *hash = finish_u64::<B>(state);
+        }
+    }
+
+    let offset = values.len() - value_groups.remainder().len();
+    for (i, (&value, hash)) in value_groups.remainder().iter().zip(hash_groups.into_remainder()).enumerate() {
+        let index = offset + i;
+        let valid = match validity {
+            Some(validity) => (validity[index / 8] >> (index % 8)) & 1 != 0,
+            None => true,
+        };
+        let state = start(*hash);
+        *hash = finish_u64::<B>(if valid { absorb_value(state, value) } else { state });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::*;
+    use core::hash::{BuildHasher, Hasher};
+    use rand::Rng;
+
+    fn is_valid(validity: &[u8], index: usize) -> bool {
+        (validity[index / 8] >> (index % 8)) & 1 != 0
+    }
+
+    #[test]
+    fn column_hashes_are_single_hashes() {
+        let mut rng = rand::thread_rng();
+        // Every group size and remainder
+        for count in 0..=2 * LANES + 1 {
+            let values: Vec<u128> = (0..count).map(|_| rng.gen()).collect();
+            let mut hashes = vec![0u64; count];
+
+            let column: Vec<u32> = values.iter().map(|&v| v as u32).collect();
+            hash_u32_column(&column, 42, &mut hashes);
+            assert!(column.iter().zip(&hashes).all(|(&v, &h)| hash_u32(v, 42) == h), "count {count}");
+
+            let column: Vec<u64> = values.iter().map(|&v| v as u64).collect();
+            hash_u64_column(&column, 42, &mut hashes);
+            assert!(column.iter().zip(&hashes).all(|(&v, &h)| hash_u64(v, 42) == h), "count {count}");
+
+            let column: Vec<i64> = values.iter().map(|&v| v as i64).collect();
+            hash_i64_column(&column, 42, &mut hashes);
+            assert!(column.iter().zip(&hashes).all(|(&v, &h)| hash_u64(v as u64, 42) == h), "count {count}");
+
+            hash_u128_column(&values, 42, &mut hashes);
+            assert!(values.iter().zip(&hashes).all(|(&v, &h)| hash_u128(v, 42) == h), "count {count}");
+
+            let column: Vec<f64> = values.iter().map(|&v| v as u64 as f64).collect();
+            hash_f64_column(&column, 42, &mut hashes);
+            assert!(column.iter().zip(&hashes).all(|(&v, &h)| hash_u64(v.to_bits(), 42) == h), "count {count}");
+        }
+    }
+
+    #[test]
+    fn combined_hashes_are_tuple_hashes() {
+        let mut rng = rand::thread_rng();
+        let build_hasher = GxIntBuildHasher::with_seed(42);
+        for count in 0..=2 * LANES + 1 {
+            let first: Vec<u64> = (0..count).map(|_| rng.gen()).collect();
+            let second: Vec<u32> = (0..count).map(|_| rng.gen()).collect();
+            let third: Vec<u128> = (0..count).map(|_| rng.gen()).collect();
+            let mut hashes = vec![0u64; count];
+            hash_u64_column(&first, 42, &mut hashes);
+            combine_u32_column(&second, 42, &mut hashes);
+            combine_u128_column(&third, 42, &mut hashes);
+            for i in 0..count {
+                let expected = build_hasher.hash_one((build_hasher.hash_one((build_hasher.hash_one(first[i]), second[i])), third[i]));
+                assert_eq!(expected, hashes[i], "count {count}, index {i}");
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::manual_div_ceil)]
+    fn nulls_are_hashed_as_nothing_written() {
+        let mut rng = rand::thread_rng();
+        let build_hasher = GxIntBuildHasher::with_seed(42);
+        let null_hash = build_hasher.build_hasher().finish();
+        for count in 0..=2 * LANES + 1 {
+            let values: Vec<i64> = (0..count).map(|_| rng.gen()).collect();
+            let validity: Vec<u8> = (0..(count + 7) / 8).map(|_| rng.gen()).collect();
+
+            let mut hashes = vec![0u64; count];
+            hash_i64_column_nullable(&values, &validity, 42, &mut hashes);
+            for i in 0..count {
+                let expected = if is_valid(&validity, i) { build_hasher.hash_one(values[i]) } else { null_hash };
+                assert_eq!(expected, hashes[i], "count {count}, index {i}");
+            }
+
+            let previous: Vec<u64> = (0..count).map(|_| rng.gen()).collect();
+            let mut hashes = previous.clone();
+            combine_i64_column_nullable(&values, &validity, 42, &mut hashes);
+            for i in 0..count {
+                let expected = match is_valid(&validity, i) {
+                    true => build_hasher.hash_one((previous[i], values[i])),
+                    false => build_hasher.hash_one(previous[i]),
+                };
+                assert_eq!(expected, hashes[i], "count {count}, index {i}");
+            }
+        }
+    }
+
+    #[test]
+    fn null_position_matters() {
+        let mut a_null_b = [0u64; 1];
+        hash_u64_column(&[1], 0, &mut a_null_b);
+        combine_u64_column_nullable(&[0], &[0], 0, &mut a_null_b);
+        combine_u64_column(&[2], 0, &mut a_null_b);
+
+        let mut a_b_null = [0u64; 1];
+        hash_u64_column(&[1], 0, &mut a_b_null);
+        combine_u64_column(&[2], 0, &mut a_b_null);
+        combine_u64_column_nullable(&[0], &[0], 0, &mut a_b_null);
+
+        assert_ne!(a_null_b, a_b_null);
+    }
+
+    #[test]
+    fn equal_floats_hash_the_same() {
+        let mut hashes = [0u64; 4];
+        hash_f64_column(&[0.0, -0.0, f64::NAN, -f64::NAN], 0, &mut hashes);
+        assert_eq!(hashes[0], hashes[1]);
+        assert_eq!(hashes[2], hashes[3]);
+        assert_ne!(hashes[0], hashes[2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn hashes_of_other_length_panics() {
+        hash_u64_column(&[1, 2], 42, &mut [0u64; 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn short_validity_panics() {
+        hash_u64_column_nullable(&[0; 9], &[u8::MAX], 42, &mut [0u64; 9]);
+    }
+}
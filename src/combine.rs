@@ -0,0 +1,138 @@
+use crate::gxhash::platform::*;
+
+/// Combines two 128-bit hashes into one, for tree or composite hashes (per-chunk hashes into a file hash,
+/// per-field hashes into a row hash, ...).
+///
+/// The combination is order-dependent: `combine(a, b)` and `combine(b, a)` are different (unless by chance).
+///
+/// # Collisions
+/// For a fixed `a`, `b -> combine(a, b)` is a bijection, and so is `a -> combine(a, b)` for a fixed `b`:
+/// two pairs that differ by a single operand never collide. Pairs that differ by both operands collide
+/// with a probability of about 2<sup>-128</sup>, as for random hashes.
+///
+/// # Example
+///
+/// ```
+/// let chunk1 = gxhash::gxhash128(b"Hello ", 1234);
+/// let chunk2 = gxhash::gxhash128(b"World", 1234);
+/// println!("Hash is {:x}!", gxhash::combine(chunk1, chunk2));
+/// ```
+#[inline(always)]
+pub fn combine(a: u128, b: u128) -> u128 {
+    dispatch!(B => unsafe { to_u128(combine_states::<B>(load_u128(a), load_u128(b))) })
+}
+
+/// Combines two 64-bit hashes into one. See [`combine`].
+///
+/// As the combination is truncated to 64 bits, pairs that differ by a single operand may collide,
+/// with a probability of about 2<sup>-64</sup>.
+#[inline(always)]
+pub fn combine64(a: u64, b: u64) -> u64 {
+    dispatch!(B => unsafe { to_u128(combine_states::<B>(load_u64(a), load_u64(b))) as u64 })
+}
+
+/// Combines two 32-bit hashes into one. See [`combine`].
+///
+/// As the combination is truncated to 32 bits, pairs that differ by a single operand may collide,
+/// with a probability of about 2<sup>-32</sup>.
+#[inline(always)]
+pub fn combine32(a: u32, b: u32) -> u32 {
+    dispatch!(B => unsafe { to_u128(combine_states::<B>(load_u32(a), load_u32(b))) as u32 })
+}
+
+/// Combines a sequence of 128-bit hashes into one. The combination is order-dependent.
+///
+/// The number of hashes is absorbed first, so that sequences of different lengths are independent:
+/// `combine_many(&[a, b])` is not the same as `combine_many(&[combine(a, b)])` (nor as `combine(a, b)`).
+/// Sequences of the same length that differ by a single hash never collide.
+///
+/// # Example
+///
+/// ```
+/// let fields = [gxhash::gxhash128(b"id", 0), gxhash::gxhash128(b"name", 0), gxhash::gxhash128(b"email", 0)];
+/// println!("Row hash is {:x}!", gxhash::combine_many(&fields));
+/// ```
+#[inline]
+pub fn combine_many(hashes: &[u128]) -> u128 {
+    dispatch!(B => unsafe { to_u128(combine_many_states::<B>(hashes.len(), hashes.iter().map(|&h| load_u128(h)))) })
+}
+
+/// Combines a sequence of 64-bit hashes into one. See [`combine_many`].
+#[inline]
+pub fn combine_many64(hashes: &[u64]) -> u64 {
+    dispatch!(B => unsafe { to_u128(combine_many_states::<B>(hashes.len(), hashes.iter().map(|&h| load_u64(h)))) as u64 })
+}
+
+/// Combines a sequence of 32-bit hashes into one. See [`combine_many`].
+#[inline]
+pub fn combine_many32(hashes: &[u32]) -> u32 {
+    dispatch!(B => unsafe { to_u128(combine_many_states::<B>(hashes.len(), hashes.iter().map(|&h| load_u32(h)))) as u32 })
+}
+
+// The left operand goes through a keyed AES round before the right operand is xored in. An AES round
+// being a permutation, the combination is a bijection of each operand.
+#[inline(always)]
+pub(crate) unsafe fn combine_states<B: Aes>(a: State, b: State) -> State {
+    finalize::<B>(B::aes_encrypt(B::aes_encrypt(a, ld(KEYS_256.as_ptr())), b))
+}
+
+#[inline(always)]
+unsafe fn combine_many_states<B: Aes>(count: usize, hashes: impl Iterator<Item = State>) -> State {
+    let mut state = load_u64(count as u64);
+    for hash in hashes {
+        state = B::aes_encrypt(B::aes_encrypt(state, ld(KEYS_256.as_ptr())), hash);
+    }
+    finalize::<B>(state)
+}
+
+#[inline(always)]
+unsafe fn to_u128(state: State) -> u128 {
+    let p = &state as *const State as *const u128;
+    u128::from_le(*p)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn combine_is_order_dependent() {
+        let a = crate::gxhash128(b"Hello", 0);
+        let b = crate::gxhash128(b"World", 0);
+        assert_ne!(combine(a, b), combine(b, a));
+        assert_ne!(combine64(a as u64, b as u64), combine64(b as u64, a as u64));
+        assert_ne!(combine32(a as u32, b as u32), combine32(b as u32, a as u32));
+        assert_ne!(combine_many(&[a, b]), combine_many(&[b, a]));
+        assert_ne!(combine(0, 0), 0);
+    }
+
+    #[test]
+    fn combine_has_no_single_operand_collisions() {
+        let mut set = std::collections::HashSet::new();
+        for x in 0..1000u128 {
+            assert!(set.insert(combine(42, x)));
+            assert!(set.insert(combine(x << 64, 42)));
+        }
+    }
+
+    #[test]
+    fn combine_many_separates_lengths() {
+        let (a, b) = (1u128, 2u128);
+        assert_ne!(combine_many(&[a, b]), combine_many(&[combine(a, b)]));
+        assert_ne!(combine_many(&[a, b]), combine(a, b));
+        assert_ne!(combine_many(&[]), combine_many(&[0]));
+        assert_ne!(combine_many(&[0]), combine_many(&[0, 0]));
+    }
+
+    #[test]
+    fn combine_is_stable() {
+        assert_eq!(52610714277749344835458390503559509920, combine(0, 0));
+        assert_eq!(284627611975381344999776795797593269847, combine(u128::MAX, 42));
+        assert_eq!(3657407806401636085, combine64(42, 43));
+        assert_eq!(3481992206, combine32(42, 43));
+        assert_eq!(245935034951084069554140236501154528002, combine_many(&[1, 2, 3]));
+        assert_eq!(6125104084643655073, combine_many64(&[1, 2, 3]));
+        assert_eq!(188782900, combine_many32(&[]));
+    }
+}
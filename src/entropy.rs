@@ -0,0 +1,357 @@
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// A user-supplied source of random seeds. See [`set_entropy_source`].
+pub type EntropySource = fn() -> u128;
+
+static ENTROPY_SOURCE: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Sets the source of random seeds used by [`GxBuildHasher::default()`](crate::GxBuildHasher::default),
+/// in place of the built-in ones. The source is called once per builder.
+///
+/// This is mostly useful in `no_std` environments, where the only built-in source is the `RDRAND`
+/// instruction on x86: a hardware RNG peripheral, a bootloader-provided seed, ... can be plugged in here.
+///
+/// # Example
+///
+/// ```
+/// fn hardware_rng() -> u128 {
+///     // Read from a TRNG peripheral
+///     # 0x0123456789abcdef0123456789abcdef
+/// }
+///
+/// gxhash::set_entropy_source(hardware_rng);
+/// ```
+#[inline]
+pub fn set_entropy_source(source: EntropySource) {
+    ENTROPY_SOURCE.store(source as *mut (), Ordering::Release);
+}
+
+/// Returns a random seed from the user-supplied source if any, or from a built-in source.
+#[inline]
+pub(crate) fn random_u128() -> Option<u128> {
+    let source = ENTROPY_SOURCE.load(Ordering::Acquire);
+    if !source.is_null() {
+        let source: EntropySource = unsafe { core::mem::transmute::<*mut (), EntropySource>(source) };
+        return Some(source());
+    }
+    builtin_random_u128()
+}
+
+/// Returns the seed set with the `GXHASH_SEED` environment variable, if any. The variable is read once.
+///
+/// # Panics
+/// Panics if the variable is set but is not a valid seed.
+#[cfg(feature = "std")]
+pub(crate) fn env_seed() -> Option<u128> {
+    use core::sync::atomic::{AtomicU64, AtomicU8};
+
+    const UNINITIALIZED: u8 = 0;
+    const UNSET: u8 = 1;
+    const SET: u8 = 2;
+
+    static STATUS: AtomicU8 = AtomicU8::new(UNINITIALIZED);
+    static SEED_LO: AtomicU64 = AtomicU64::new(0);
+    static SEED_HI: AtomicU64 = AtomicU64::new(0);
+
+    match STATUS.load(Ordering::Acquire) {
+        UNSET => return None,
+        SET => return Some((SEED_HI.load(Ordering::Relaxed) as u128) << 64 | SEED_LO.load(Ordering::Relaxed) as u128),
+        _ => {}
+    }
+
+    // Concurrent first calls read the same value, so racing here is harmless
+    let seed = std::env::var("GXHASH_SEED").ok().map(|value| {
+        parse_seed(&value).unwrap_or_else(|| panic!("GXHASH_SEED must be a decimal or 0x-prefixed hexadecimal 128-bit integer, got {value:?}"))
+    });
+    match seed {
+        Some(seed) => {
+            SEED_LO.store(seed as u64, Ordering::Relaxed);
+            SEED_HI.store((seed >> 64) as u64, Ordering::Relaxed);
+            STATUS.store(SET, Ordering::Release);
+        }
+        None => STATUS.store(UNSET, Ordering::Release),
+    }
+    seed
+}
+
+#[cfg(feature = "std")]
+fn parse_seed(value: &str) -> Option<u128> {
+    let value = value.trim();
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u128::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn builtin_random_u128() -> Option<u128> {
+    Some(seeding::next_seed())
+}
+
+// OS randomness is read once per process into a root key. Each thread derives its own key from the root key
+// and a thread index, and each builder gets a seed derived from the thread key and a counter, so that seeding
+// is only a few AES rounds once the thread key is known. On unix, forking bumps a generation counter so that
+// child processes re-read OS randomness instead of sharing their parent's keys.
+#[cfg(feature = "std")]
+mod seeding {
+    use core::cell::Cell;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::gxhash::platform::*;
+
+    const UNINITIALIZED: u64 = u64::MAX;
+
+    static GENERATION: AtomicU64 = AtomicU64::new(0);
+    static ROOT_GENERATION: AtomicU64 = AtomicU64::new(UNINITIALIZED);
+    static ROOT_LO: AtomicU64 = AtomicU64::new(0);
+    static ROOT_HI: AtomicU64 = AtomicU64::new(0);
+    static THREAD_COUNT: AtomicU64 = AtomicU64::new(0);
+
+    #[derive(Clone, Copy)]
+    struct ThreadSeed {
+        key: u128,
+        counter: u64,
+        generation: u64,
+    }
+
+    thread_local! {
+        static THREAD_SEED: Cell<ThreadSeed> = const { Cell::new(ThreadSeed { key: 0, counter: 0, generation: UNINITIALIZED }) };
+    }
+
+    #[inline]
+    pub fn next_seed() -> u128 {
+        THREAD_SEED.with(|cell| {
+            let mut seed = cell.get();
+            let generation = GENERATION.load(Ordering::Acquire);
+            if seed.generation != generation {
+                seed = ThreadSeed { key: thread_key(generation), counter: 0, generation };
+            }
+            seed.counter += 1;
+            cell.set(seed);
+            dispatch!(B => unsafe { to_u128(derive::<B>(seed.key, seed.counter)) })
+        })
+    }
+
+    #[cold]
+    fn thread_key(generation: u64) -> u128 {
+        let root = root_key(generation);
+        let index = THREAD_COUNT.fetch_add(1, Ordering::Relaxed);
+        // Mixes in the generation too, as thread indices are inherited by child processes
+        dispatch!(B => unsafe { to_u128(derive::<B>(root, index ^ generation.rotate_left(32))) })
+    }
+
+    fn root_key(generation: u64) -> u128 {
+        if ROOT_GENERATION.load(Ordering::Acquire) == generation {
+            return (ROOT_HI.load(Ordering::Relaxed) as u128) << 64 | ROOT_LO.load(Ordering::Relaxed) as u128;
+        }
+        fork::register();
+        // Racing threads may store different halves, which are random all the same
+        let root = os_random_u128();
+        ROOT_LO.store(root as u64, Ordering::Relaxed);
+        ROOT_HI.store((root >> 64) as u64, Ordering::Relaxed);
+        ROOT_GENERATION.store(generation, Ordering::Release);
+        root
+    }
+
+    #[inline(always)]
+    unsafe fn derive<B: Aes>(key: u128, counter: u64) -> State {
+        let key = load_u128(key);
+        finalize::<B>(B::aes_encrypt(B::aes_encrypt(load_u64(counter), key), key))
+    }
+
+    #[inline(always)]
+    unsafe fn to_u128(state: State) -> u128 {
+        let p = &state as *const State as *const u128;
+        u128::from_le(*p)
+    }
+
+    #[cfg(unix)]
+    fn os_random_u128() -> u128 {
+        use std::io::Read;
+
+        let mut bytes = [0u8; 16];
+        match std::fs::File::open("/dev/urandom").and_then(|mut file| file.read_exact(&mut bytes)) {
+            Ok(()) => u128::from_le_bytes(bytes),
+            // For instance in a chroot without /dev
+            Err(_) => random_state_u128(),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn os_random_u128() -> u128 {
+        random_state_u128()
+    }
+
+    #[rustversion::before(1.76)]
+    use std::collections::hash_map::RandomState;
+
+    #[rustversion::since(1.76)]
+    use std::hash::RandomState;
+
+    // The standard library seeds RandomState from the OS, through its public API only
+    fn random_state_u128() -> u128 {
+        use core::hash::{BuildHasher, Hasher};
+
+        let lo = RandomState::new().build_hasher().finish();
+        let hi = RandomState::new().build_hasher().finish();
+        (hi as u128) << 64 | lo as u128
+    }
+
+    #[cfg(unix)]
+    pub(super) mod fork {
+        use super::*;
+        use std::sync::Once;
+
+        extern "C" {
+            fn pthread_atfork(
+                prepare: Option<unsafe extern "C" fn()>,
+                parent: Option<unsafe extern "C" fn()>,
+                child: Option<unsafe extern "C" fn()>,
+            ) -> core::ffi::c_int;
+        }
+
+        pub fn register() {
+            static REGISTER: Once = Once::new();
+            REGISTER.call_once(|| unsafe {
+                pthread_atfork(None, None, Some(on_fork_child));
+            });
+        }
+
+        // Only touches an atomic, which is async-signal-safe
+        unsafe extern "C" fn on_fork_child() {
+            GENERATION.fetch_add(1, Ordering::Release);
+        }
+    }
+
+    #[cfg(not(unix))]
+    mod fork {
+        pub fn register() {}
+    }
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+        use std::collections::HashSet;
+
+        #[test]
+        fn seeds_are_unique_across_threads() {
+            let seeds: Vec<u128> = (0..8)
+                .map(|_| std::thread::spawn(|| (0..1000).map(|_| next_seed()).collect::<Vec<_>>()))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect();
+            let unique: HashSet<u128> = seeds.iter().copied().collect();
+            assert_eq!(seeds.len(), unique.len());
+        }
+
+        #[test]
+        fn random_state_fallback_is_random() {
+            assert_ne!(random_state_u128(), random_state_u128());
+        }
+    }
+}
+
+#[cfg(all(not(feature = "std"), any(target_arch = "x86", target_arch = "x86_64")))]
+#[inline]
+fn builtin_random_u128() -> Option<u128> {
+    rdrand::random_u128()
+}
+
+#[cfg(all(not(feature = "std"), not(any(target_arch = "x86", target_arch = "x86_64"))))]
+#[inline]
+fn builtin_random_u128() -> Option<u128> {
+    None
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[allow(dead_code)]
+mod rdrand {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    // Intel recommends retrying a few times, as RDRAND may transiently fail when the DRNG is exhausted
+    const RETRIES: usize = 10;
+
+    pub fn random_u128() -> Option<u128> {
+        if !is_supported() {
+            return None;
+        }
+        unsafe { Some((random_u64()? as u128) << 64 | random_u64()? as u128) }
+    }
+
+    #[cfg(feature = "std")]
+    fn is_supported() -> bool {
+        is_x86_feature_detected!("rdrand")
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[allow(unused_unsafe)]
+    fn is_supported() -> bool {
+        // CPUID.01H:ECX.RDRAND[bit 30]
+        unsafe { __cpuid(1).ecx & (1 << 30) != 0 }
+    }
+
+    #[target_feature(enable = "rdrand")]
+    unsafe fn random_u64() -> Option<u64> {
+        for _ in 0..RETRIES {
+            let mut value = 0;
+            #[cfg(target_arch = "x86_64")]
+            let success = _rdrand64_step(&mut value) == 1;
+            #[cfg(target_arch = "x86")]
+            let success = {
+                let (mut lo, mut hi) = (0, 0);
+                let success = _rdrand32_step(&mut lo) == 1 && _rdrand32_step(&mut hi) == 1;
+                value = (hi as u64) << 32 | lo as u64;
+                success
+            };
+            // Some AMD processors return all ones after a resume from suspend
+            if success && value != u64::MAX {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn rdrand_is_random() {
+        if !is_x86_feature_detected!("rdrand") {
+            return;
+        }
+        let a = rdrand::random_u128().unwrap();
+        let b = rdrand::random_u128().unwrap();
+        assert_ne!(a, b);
+        assert_ne!(a as u64, (a >> 64) as u64);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn env_seed_is_parsed() {
+        assert_eq!(Some(42), parse_seed("42"));
+        assert_eq!(Some(42), parse_seed(" 42\n"));
+        assert_eq!(Some(0x2a), parse_seed("0x2a"));
+        assert_eq!(Some(0x2A), parse_seed("0X2A"));
+        assert_eq!(Some(u128::MAX), parse_seed("0xffffffffffffffffffffffffffffffff"));
+        assert_eq!(None, parse_seed(""));
+        assert_eq!(None, parse_seed("-1"));
+        assert_eq!(None, parse_seed("0x"));
+        assert_eq!(None, parse_seed("seed"));
+        assert_eq!(None, parse_seed("0x100000000000000000000000000000000"));
+    }
+
+    #[test]
+    fn builtin_source_is_random() {
+        assert_ne!(builtin_random_u128(), builtin_random_u128());
+    }
+}
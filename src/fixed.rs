@@ -0,0 +1,113 @@
+use crate::gxhash::platform::*;
+use crate::gxhash::*;
+
+/// Hashes a fixed-size input to an u32. The hash is the same as [`gxhash32`] on the same bytes.
+///
+/// As the length is known at compile time, the length checks of [`gxhash32`] are resolved at compile time,
+/// and small inputs are loaded without checking whether reading beyond them is safe.
+///
+/// # Example
+///
+/// ```
+/// let key = [42u8; 12];
+/// assert_eq!(gxhash::gxhash32(&key, 1234), gxhash::gxhash32_fixed(&key, 1234));
+/// ```
+#[inline(always)]
+pub fn gxhash32_fixed<const N: usize>(input: &[u8; N], seed: i64) -> u32 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_fixed::<B, N>(input, create_seed(seed)) as *const State as *const u32;
+        u32::from_le(*p)
+    })
+}
+
+/// Hashes a fixed-size input to an u64. The hash is the same as [`gxhash64`] on the same bytes.
+///
+/// As the length is known at compile time, the length checks of [`gxhash64`] are resolved at compile time,
+/// and small inputs are loaded without checking whether reading beyond them is safe.
+///
+/// # Example
+///
+/// ```
+/// let key = [42u8; 12];
+/// assert_eq!(gxhash::gxhash64(&key, 1234), gxhash::gxhash64_fixed(&key, 1234));
+/// ```
+#[inline(always)]
+pub fn gxhash64_fixed<const N: usize>(input: &[u8; N], seed: i64) -> u64 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_fixed::<B, N>(input, create_seed(seed)) as *const State as *const u64;
+        u64::from_le(*p)
+    })
+}
+
+/// Hashes a fixed-size input to an u128. The hash is the same as [`gxhash128`] on the same bytes.
+///
+/// As the length is known at compile time, the length checks of [`gxhash128`] are resolved at compile time,
+/// and small inputs are loaded without checking whether reading beyond them is safe.
+///
+/// # Example
+///
+/// ```
+/// let key = [42u8; 12];
+/// assert_eq!(gxhash::gxhash128(&key, 1234), gxhash::gxhash128_fixed(&key, 1234));
+/// ```
+#[inline(always)]
+pub fn gxhash128_fixed<const N: usize>(input: &[u8; N], seed: i64) -> u128 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_fixed::<B, N>(input, create_seed(seed)) as *const State as *const u128;
+        u128::from_le(*p)
+    })
+}
+
+#[inline(always)]
+unsafe fn gxhash_fixed<B: Aes, const N: usize>(input: &[u8; N], seed: State) -> State {
+    let compressed = if N == 0 {
+        create_empty()
+    } else if N <= VECTOR_SIZE {
+        // Copying a constant number of bytes compiles to a few moves, no page check is needed
+        get_partial_safe(input.as_ptr() as *const State, N)
+    } else {
+        // Inlined with a constant length, so that all length branches are folded
+        compress_all_with::<B>(input, &KEYS, Profile::Default)
+    };
+    finalize_with::<B>(B::aes_encrypt(compressed, seed), &KEYS, Profile::Default)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::Rng;
+
+    // Not generic, so that the slice hash is compiled only once
+    fn slice_hash(input: &[u8]) -> u128 {
+        gxhash128(input, 42)
+    }
+
+    fn check_size<const N: usize>() {
+        let mut input = [0u8; N];
+        rand::thread_rng().fill(input.as_mut_slice());
+        assert_eq!(slice_hash(&input), gxhash128_fixed(&input, 42), "size {N}");
+    }
+
+    macro_rules! check_sizes {
+        ($($n:literal),+) => {
+            $(check_size::<$n>();)+
+        };
+    }
+
+    #[test]
+    fn fixed_hashes_are_slice_hashes() {
+        // Every size up to two vectors, and around the boundaries of the other length branches
+        check_sizes!(
+            0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26,
+            27, 28, 29, 30, 31, 32, 33, 47, 48, 49, 63, 64, 65, 79, 80, 81, 127, 128, 129
+        );
+    }
+
+    #[test]
+    fn fixed_hashes_are_truncated_consistently() {
+        let input = [42u8; 20];
+        assert_eq!(gxhash32(&input, 42), gxhash32_fixed(&input, 42));
+        assert_eq!(gxhash64(&input, 42), gxhash64_fixed(&input, 42));
+    }
+}
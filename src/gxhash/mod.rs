@@ -13,10 +13,10 @@ use platform::*;
 /// ```
 #[inline(always)]
 pub fn gxhash32(input: &[u8], seed: i64) -> u32 {
-    unsafe {
-        let p = &gxhash(input, create_seed(seed)) as *const State as *const u32;
-        *p
-    }
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, create_seed(seed)) as *const State as *const u32;
+        u32::from_le(*p)
+    })
 }
 
 /// Hashes an arbitrary stream of bytes to an u64.
@@ -30,10 +30,10 @@ pub fn gxhash32(input: &[u8], seed: i64) -> u32 {
 /// ```
 #[inline(always)]
 pub fn gxhash64(input: &[u8], seed: i64) -> u64 {
-    unsafe {
-        let p = &gxhash(input, create_seed(seed)) as *const State as *const u64;
-        *p
-    }
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, create_seed(seed)) as *const State as *const u64;
+        u64::from_le(*p)
+    })
 }
 
 /// Hashes an arbitrary stream of bytes to an u128.
@@ -47,10 +47,175 @@ pub fn gxhash64(input: &[u8], seed: i64) -> u64 {
 /// ```
 #[inline(always)]
 pub fn gxhash128(input: &[u8], seed: i64) -> u128 {
-    unsafe {
-        let p = &gxhash(input, create_seed(seed)) as *const State as *const u128;
-        *p
-    }
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, create_seed(seed)) as *const State as *const u128;
+        u128::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u32, using a 128-bit seed.
+///
+/// Unlike [`gxhash32`] where the 64-bit seed is duplicated in both halves of the internal state,
+/// the seed is expanded through AES rounds so that all 128 bits of the state depend on it.
+///
+/// # Example
+///
+/// ```
+/// let bytes = [42u8; 1000];
+/// let seed = 0x0123456789abcdef0123456789abcdef;
+/// println!("Hash is {:x}!", gxhash::gxhash32_seeded(&bytes, seed));
+/// ```
+#[inline(always)]
+pub fn gxhash32_seeded(input: &[u8], seed: u128) -> u32 {
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, create_seed128::<B>(seed)) as *const State as *const u32;
+        u32::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u64, using a 128-bit seed.
+///
+/// Unlike [`gxhash64`] where the 64-bit seed is duplicated in both halves of the internal state,
+/// the seed is expanded through AES rounds so that all 128 bits of the state depend on it.
+///
+/// # Example
+///
+/// ```
+/// let bytes = [42u8; 1000];
+/// let seed = 0x0123456789abcdef0123456789abcdef;
+/// println!("Hash is {:x}!", gxhash::gxhash64_seeded(&bytes, seed));
+/// ```
+#[inline(always)]
+pub fn gxhash64_seeded(input: &[u8], seed: u128) -> u64 {
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, create_seed128::<B>(seed)) as *const State as *const u64;
+        u64::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u128, using a 128-bit seed.
+///
+/// Unlike [`gxhash128`] where the 64-bit seed is duplicated in both halves of the internal state,
+/// the seed is expanded through AES rounds so that all 128 bits of the state depend on it.
+///
+/// # Example
+///
+/// ```
+/// let bytes = [42u8; 1000];
+/// let seed = 0x0123456789abcdef0123456789abcdef;
+/// println!("Hash is {:x}!", gxhash::gxhash128_seeded(&bytes, seed));
+/// ```
+#[inline(always)]
+pub fn gxhash128_seeded(input: &[u8], seed: u128) -> u128 {
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, create_seed128::<B>(seed)) as *const State as *const u128;
+        u128::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an output of arbitrary length (extendable-output mode).
+///
+/// The input is compressed once, then as many 16-byte blocks as needed are squeezed out of the
+/// compressed state, each being finalized with a block counter mixed in. Outputs are prefix-consistent:
+/// the first 16 bytes are the same as [`gxhash128`] (in little-endian order), and a shorter output is
+/// always a prefix of a longer one.
+///
+/// # Example
+///
+/// ```
+/// let bytes = [42u8; 1000];
+/// let seed = 1234;
+/// let mut fingerprint = [0u8; 64];
+/// gxhash::gxhash_xof(&bytes, seed, &mut fingerprint);
+/// assert_eq!(fingerprint[..16], gxhash::gxhash128(&bytes, seed).to_le_bytes());
+/// ```
+#[inline]
+pub fn gxhash_xof(input: &[u8], seed: i64, output: &mut [u8]) {
+    dispatch!(B => unsafe {
+        let state = B::aes_encrypt(compress_all_with::<B>(input, &KEYS, Profile::Default), create_seed(seed));
+        squeeze::<B>(state, output, Profile::Default);
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to 256 bits.
+///
+/// Both 128-bit lanes of the compression are kept (or derived, for small inputs) and finalized with
+/// domain-separated round keys. The first 16 bytes are the same as [`gxhash128`] (in little-endian order).
+/// Hashes are stable between the `hybrid` and non-hybrid builds, and across all supported platforms.
+///
+/// # Example
+///
+/// ```
+/// let bytes = [42u8; 1000];
+/// let seed = 1234;
+/// let hash: [u8; 32] = gxhash::gxhash256(&bytes, seed);
+/// ```
+#[inline(always)]
+pub fn gxhash256(input: &[u8], seed: i64) -> [u8; 32] {
+    dispatch!(B => unsafe {
+        let seed = create_seed(seed);
+        let (hash1, hash2) = compress_all_256::<B>(input);
+        let hash1 = finalize_with::<B>(B::aes_encrypt(hash1, seed), &KEYS, Profile::Default);
+        let hash2 = finalize_with::<B>(B::aes_encrypt(hash2, seed), &KEYS_256, Profile::Default);
+        core::mem::transmute::<[State; 2], [u8; 32]>([hash1, hash2])
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u32, in the key space identified by the given domain label.
+///
+/// Different domains produce uncorrelated hashes for a same seed. The label is hashed on every call,
+/// prefer [`GxBuildHasher::derive`](crate::GxBuildHasher::derive) when hashing many inputs in a same domain.
+///
+/// # Example
+///
+/// ```
+/// let seed = 1234;
+/// println!("Hash is {:x}!", gxhash::gxhash32_with_domain(b"user-42", seed, b"sessions"));
+/// ```
+#[inline(always)]
+pub fn gxhash32_with_domain(input: &[u8], seed: i64, domain: &[u8]) -> u32 {
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, derive_state::<B>(create_seed(seed), domain)) as *const State as *const u32;
+        u32::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u64, in the key space identified by the given domain label.
+///
+/// Different domains produce uncorrelated hashes for a same seed. The label is hashed on every call,
+/// prefer [`GxBuildHasher::derive`](crate::GxBuildHasher::derive) when hashing many inputs in a same domain.
+///
+/// # Example
+///
+/// ```
+/// let seed = 1234;
+/// println!("Hash is {:x}!", gxhash::gxhash64_with_domain(b"user-42", seed, b"sessions"));
+/// ```
+#[inline(always)]
+pub fn gxhash64_with_domain(input: &[u8], seed: i64, domain: &[u8]) -> u64 {
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, derive_state::<B>(create_seed(seed), domain)) as *const State as *const u64;
+        u64::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u128, in the key space identified by the given domain label.
+///
+/// Different domains produce uncorrelated hashes for a same seed. The label is hashed on every call,
+/// prefer [`GxBuildHasher::derive`](crate::GxBuildHasher::derive) when hashing many inputs in a same domain.
+///
+/// # Example
+///
+/// ```
+/// let seed = 1234;
+/// println!("Hash is {:x}!", gxhash::gxhash128_with_domain(b"user-42", seed, b"sessions"));
+/// ```
+#[inline(always)]
+pub fn gxhash128_with_domain(input: &[u8], seed: i64, domain: &[u8]) -> u128 {
+    dispatch!(B => unsafe {
+        let p = &gxhash::<B>(input, derive_state::<B>(create_seed(seed), domain)) as *const State as *const u128;
+        u128::from_le(*p)
+    })
 }
 
 macro_rules! load_unaligned {
@@ -65,28 +230,149 @@ macro_rules! load_unaligned {
 
 pub(crate) use load_unaligned;
 
+/// Trade-off between latency and mixing, as the number of AES rounds used for finalizing a hash
+/// and for compressing inputs that don't go through the high ILP loop.
+///
+/// Each profile produces different hashes, which are independently stable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// Fewer rounds when compressing inputs of more than 16 bytes, for lower latency. The finalization keeps two
+    /// full rounds, as fewer don't avalanche. Suited for internal maps with trusted keys, such as short strings.
+    Fast,
+    /// The default number of rounds, used by [`gxhash32`], [`gxhash64`] and [`gxhash128`].
+    #[default]
+    Default,
+    /// More rounds, for maps facing untrusted input.
+    Hardened,
+}
+
+/// Implementation used for hashing, as reported by [`backend`]. All backends produce the same hashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Backend {
+    /// 256-bit VAES and AVX2 instructions on x86 (requires the `hybrid` feature).
+    Vaes,
+    /// 128-bit AES-NI and SSE2 instructions on x86.
+    Aes,
+    /// AES and NEON instructions on ARM.
+    Neon,
+    /// Software emulation of AES, when hardware acceleration is not available.
+    Portable,
+}
+
+/// Returns the backend used for hashing.
+///
+/// On x86 with the `std` feature, CPU features that are not enabled at compile time are detected at
+/// run time, so this may be a faster backend than the build target guarantees.
+///
+/// # Example
+///
+/// ```
+/// println!("Hashing with {:?}", gxhash::backend());
+/// ```
+#[inline]
+pub fn backend() -> Backend {
+    active_backend()
+}
+
+#[inline(always)]
+pub(crate) unsafe fn gxhash<B: Aes>(input: &[u8], seed: State) -> State {
+    gxhash_with::<B>(input, seed, &KEYS, Profile::Default)
+}
+
 #[inline(always)]
-pub(crate) unsafe fn gxhash(input: &[u8], seed: State) -> State {
-    finalize(aes_encrypt(compress_all(input), seed))
+pub(crate) unsafe fn gxhash_with<B: Aes>(input: &[u8], seed: State, keys: &[u32; 12], profile: Profile) -> State {
+    finalize_with::<B>(B::aes_encrypt(compress_all_with::<B>(input, keys, profile), seed), keys, profile)
 }
 
+/// Absorbs a domain label into a seed state. The label is hashed with the round keys of the second half of
+/// 256-bit hashes, so that the derived state is not the 128-bit hash of the label.
 #[inline(always)]
-pub(crate) unsafe fn compress_all(input: &[u8]) -> State {
+pub(crate) unsafe fn derive_state<B: Aes>(seed: State, domain: &[u8]) -> State {
+    gxhash_with::<B>(domain, seed, &KEYS_256, Profile::Default)
+}
+
+/// Fills the output with blocks finalized from the given state. The first block is the state finalized
+/// as is, so that the output is prefix-consistent with the 128-bit hash.
+#[inline(always)]
+pub(crate) unsafe fn squeeze<B: Aes>(state: State, output: &mut [u8], profile: Profile) {
+    for (i, chunk) in output.chunks_mut(VECTOR_SIZE).enumerate() {
+        let block = if i == 0 { state } else { B::aes_encrypt(state, load_u64(i as u64)) };
+        let block = core::mem::transmute::<State, [u8; VECTOR_SIZE]>(finalize_with::<B>(block, &KEYS, profile));
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+#[inline(always)]
+pub(crate) unsafe fn compress_all_with<B: Aes>(input: &[u8], keys: &[u32; 12], profile: Profile) -> State {
+
+    let (hash_vector, mut v0) = match compress_parts::<B>(input, keys) {
+        Compressed::Vector(vector) => return vector,
+        Compressed::Short { hash_vector, v0 } => (hash_vector, v0),
+        // Merge lanes
+        Compressed::Lanes { lane1, lane2, v0 } => (B::aes_encrypt(lane1, lane2), v0),
+    };
+
+    v0 = B::aes_encrypt(v0, ld(keys.as_ptr()));
+    match profile {
+        Profile::Fast => {}
+        Profile::Default => {
+            v0 = B::aes_encrypt(v0, ld(keys.as_ptr().offset(4)));
+        }
+        Profile::Hardened => {
+            v0 = B::aes_encrypt(v0, ld(keys.as_ptr().offset(4)));
+            v0 = B::aes_encrypt(v0, ld(keys.as_ptr().offset(8)));
+            v0 = B::aes_encrypt(v0, ld(keys.as_ptr()));
+        }
+    }
+
+    B::aes_encrypt_last(hash_vector, v0)
+}
+
+/// Same as [`compress_all_with`] with the default round keys and profile, but keeping two 128-bit hashes.
+/// The first one is the same as the one returned by [`compress_all_with`].
+#[inline(always)]
+pub(crate) unsafe fn compress_all_256<B: Aes>(input: &[u8]) -> (State, State) {
+
+    let (hash_vector, hash_vector2, v0) = match compress_parts::<B>(input, &KEYS) {
+        // Both hashes are the same at this point, they are differentiated by their round keys
+        Compressed::Vector(vector) => return (vector, vector),
+        Compressed::Short { hash_vector, v0 } => (hash_vector, hash_vector, v0),
+        // Both lanes are kept, and merged in a different order for each hash
+        Compressed::Lanes { lane1, lane2, v0 } => (B::aes_encrypt(lane1, lane2), B::aes_encrypt(lane2, lane1), v0),
+    };
+
+    (B::aes_encrypt_last(hash_vector, B::aes_encrypt(B::aes_encrypt(v0, ld(KEYS.as_ptr())), ld(KEYS.as_ptr().offset(4)))),
+     B::aes_encrypt_last(hash_vector2, B::aes_encrypt(B::aes_encrypt(v0, ld(KEYS_256.as_ptr())), ld(KEYS_256.as_ptr().offset(4)))))
+}
+
+/// The compression of an input, before the vector of its last bytes is mixed with the round keys.
+enum Compressed {
+    /// Inputs of up to one vector, which are hashed as is
+    Vector(State),
+    /// Inputs of up to 4 vectors
+    Short { hash_vector: State, v0: State },
+    /// Larger inputs, with both lanes of the high ILP loop
+    Lanes { lane1: State, lane2: State, v0: State },
+}
+
+#[inline(always)]
+unsafe fn compress_parts<B: Aes>(input: &[u8], keys: &[u32; 12]) -> Compressed {
 
     let len = input.len();
     let mut ptr = input.as_ptr() as *const State;
 
     if len == 0 {
-        return create_empty();
+        return Compressed::Vector(create_empty());
     }
 
     if len <= VECTOR_SIZE {
         // Input fits on a single SIMD vector, however we might read beyond the input message
         // Thus we need this safe method that checks if it can safely read beyond or must copy
-        return get_partial(ptr, len);
+        return Compressed::Vector(get_partial(ptr, len));
     }
 
-    let mut hash_vector: State;
+    let hash_vector: State;
     let end = ptr as usize + len;
 
     let extra_bytes_count = len % VECTOR_SIZE;
@@ -107,26 +393,26 @@ pub(crate) unsafe fn compress_all(input: &[u8]) -> State {
     if len > VECTOR_SIZE * 2 {
         // Fast path when input length > 32 and <= 48
         load_unaligned!(ptr, v);
-        v0 = aes_encrypt(v0, v);
+        v0 = B::aes_encrypt(v0, v);
 
         if len > VECTOR_SIZE * 3 {
             // Fast path when input length > 48 and <= 64
             load_unaligned!(ptr, v);
-            v0 = aes_encrypt(v0, v);
+            v0 = B::aes_encrypt(v0, v);
 
             if len > VECTOR_SIZE * 4 {
                 // Input message is large and we can use the high ILP loop
-                hash_vector = compress_many(ptr, end, hash_vector, len);
+                let (lane1, lane2) = compress_many_lanes::<B>(ptr, end, hash_vector, len, keys);
+                return Compressed::Lanes { lane1, lane2, v0 };
             }
         }
     }
-    
-    return aes_encrypt_last(hash_vector, 
-        aes_encrypt(aes_encrypt(v0, ld(KEYS.as_ptr())), ld(KEYS.as_ptr().offset(4))));
+
+    Compressed::Short { hash_vector, v0 }
 }
 
 #[inline(always)]
-unsafe fn compress_many(mut ptr: *const State, end: usize, hash_vector: State, len: usize) -> State {
+unsafe fn compress_many_lanes<B: Aes>(mut ptr: *const State, end: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
 
     const UNROLL_FACTOR: usize = 8;
 
@@ -141,12 +427,12 @@ unsafe fn compress_many(mut ptr: *const State, end: usize, hash_vector: State, l
     let mut hash_vector = hash_vector;
     while (ptr as usize) < end_address {
         load_unaligned!(ptr, v0);
-        hash_vector = aes_encrypt(hash_vector, v0);
+        hash_vector = B::aes_encrypt(hash_vector, v0);
     }
 
     // Process the remaining n * 8 blocks
     // This part may use 128-bit or 256-bit
-    compress_8(ptr, end, hash_vector, len)
+    B::compress_8(ptr, end, hash_vector, len, keys)
 }
 
 #[cfg(test)]
@@ -155,6 +441,18 @@ mod tests {
     use super::*;
     use rand::Rng;
 
+    #[test]
+    fn backend_is_detected() {
+        let backend = backend();
+        if cfg!(feature = "force-portable") {
+            assert_eq!(Backend::Portable, backend);
+        }
+        #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64"), not(feature = "force-portable")))]
+        if is_x86_feature_detected!("aes") {
+            assert_ne!(Backend::Portable, backend);
+        }
+    }
+
     #[test]
     fn all_blocks_are_consumed() {
         for s in 1..1200 {
@@ -173,6 +471,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::unnecessary_mut_passed)]
     fn add_zeroes_mutates_hash() {
         let mut bytes = [0u8; 1200];
 
@@ -206,6 +505,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn safe_and_unsafe_partial_loads_are_identical() {
+        let mut bytes = [0u8; 64];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+
+        for offset in 0..VECTOR_SIZE {
+            for len in 0..=VECTOR_SIZE {
+                unsafe {
+                    let ptr = bytes.as_ptr().add(offset) as *const State;
+                    let safe = core::mem::transmute::<State, [u8; VECTOR_SIZE]>(get_partial_safe(ptr, len));
+                    let unsafe_ = core::mem::transmute::<State, [u8; VECTOR_SIZE]>(get_partial_unsafe(ptr, len));
+                    let partial = core::mem::transmute::<State, [u8; VECTOR_SIZE]>(get_partial(ptr, len));
+                    assert_eq!(safe, unsafe_, "offset {offset}, len {len}");
+                    assert_eq!(safe, partial, "offset {offset}, len {len}");
+                }
+            }
+        }
+    }
+
     #[test]
     fn hash_of_zero_is_not_zero() {
         assert_ne!(0, gxhash32(&[0u8; 0], 0));
@@ -223,4 +541,108 @@ mod tests {
         assert_eq!(1156095992, gxhash32(b"Hello World", i64::MAX));
         assert_eq!(540827083, gxhash32(b"Hello World", i64::MIN));
     }
+
+    #[test]
+    fn xof_is_prefix_consistent() {
+        let mut long = [0u8; 200];
+        gxhash_xof(b"Hello World", 42, &mut long);
+        assert_eq!(long[..16], gxhash128(b"Hello World", 42).to_le_bytes());
+
+        for len in 0..long.len() {
+            let mut short = vec![0u8; len];
+            gxhash_xof(b"Hello World", 42, &mut short);
+            assert_eq!(short, long[..len]);
+        }
+
+        // Blocks are all different
+        let blocks: std::collections::HashSet<&[u8]> = long.chunks_exact(16).collect();
+        assert_eq!(blocks.len(), long.len() / 16);
+    }
+
+    #[test]
+    fn xof_is_stable() {
+        let mut output = [0u8; 40];
+        gxhash_xof(&[42u8; 4242], 42, &mut output);
+        assert_eq!(output, [161, 131, 196, 247, 251, 141, 233, 239, 114, 97, 211, 246, 211, 80, 49, 142, 182, 116, 53, 137,
+                            193, 123, 148, 1, 37, 11, 197, 158, 91, 142, 114, 158, 115, 44, 97, 211, 101, 240, 11, 212]);
+    }
+
+    #[test]
+    fn gxhash256_extends_gxhash128() {
+        let mut bytes = [0u8; 1200];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+
+        for len in 0..bytes.len() {
+            let hash = gxhash256(&bytes[..len], 42);
+            assert_eq!(hash[..16], gxhash128(&bytes[..len], 42).to_le_bytes(), "Hash mismatch for input of size {len}");
+            assert_ne!(hash[..16], hash[16..]);
+        }
+    }
+
+    #[test]
+    fn gxhash256_second_half_consumes_all_blocks() {
+        for s in 1..300 {
+            let mut bytes = vec![42u8; s];
+            let ref_hash = gxhash256(&bytes, 0);
+
+            for i in 0..bytes.len() {
+                let swap = bytes[i];
+                bytes[i] = 82;
+                let new_hash = gxhash256(&bytes, 0);
+                bytes[i] = swap;
+
+                assert_ne!(ref_hash[16..], new_hash[16..], "byte {i} not processed for input of size {s}");
+            }
+        }
+    }
+
+    #[test]
+    fn gxhash256_is_stable() {
+        assert_eq!(gxhash256(&[0u8; 0], 0)[16..], [15, 35, 131, 170, 164, 243, 106, 59, 131, 198, 177, 44, 246, 188, 20, 85]);
+        assert_eq!(gxhash256(&[0u8; 1], 0)[16..], [27, 183, 17, 2, 139, 41, 21, 226, 88, 177, 169, 30, 4, 249, 161, 110]);
+        assert_eq!(gxhash256(&[0u8; 1000], 0)[16..], [199, 18, 254, 63, 98, 45, 210, 50, 208, 146, 123, 196, 139, 216, 74, 129]);
+        assert_eq!(gxhash256(&[42u8; 4242], 42)[16..], [96, 66, 218, 33, 243, 213, 82, 139, 19, 133, 153, 248, 231, 163, 184, 132]);
+        assert_eq!(gxhash256(b"Hello World", i64::MAX)[16..], [255, 246, 152, 191, 174, 186, 80, 139, 30, 26, 25, 32, 250, 170, 96, 27]);
+    }
+
+    #[test]
+    fn domains_are_separated() {
+        let bytes = b"Hello World";
+        let hash = gxhash64(bytes, 42);
+        let sessions = gxhash64_with_domain(bytes, 42, b"sessions");
+        let users = gxhash64_with_domain(bytes, 42, b"users");
+        assert_ne!(hash, sessions);
+        assert_ne!(sessions, users);
+        assert_ne!(hash, gxhash64_with_domain(bytes, 42, b""));
+        assert_ne!(sessions, gxhash64_with_domain(bytes, 43, b"sessions"));
+        assert_eq!(sessions, gxhash64_with_domain(bytes, 42, b"sessions"));
+    }
+
+    #[test]
+    fn domain_is_stable() {
+        assert_eq!(2831507654, gxhash32_with_domain(&[0u8; 0], 0, b""));
+        assert_eq!(2315478684, gxhash32_with_domain(&[0u8; 1000], 0, b"sessions"));
+        assert_eq!(1048089314, gxhash32_with_domain(b"Hello World", i64::MAX, &[42u8; 100]));
+    }
+
+    #[test]
+    fn seeded_uses_all_seed_bits() {
+        let bytes = b"Hello World";
+        let ref_hash = gxhash128_seeded(bytes, 0);
+        for bit in 0..128 {
+            assert_ne!(ref_hash, gxhash128_seeded(bytes, 1u128 << bit), "Seed bit {bit} not used");
+        }
+        // A seed with equal halves is not the same as the equivalent 64-bit seed
+        assert_ne!(gxhash128(bytes, 42), gxhash128_seeded(bytes, (42u128 << 64) | 42));
+    }
+
+    #[test]
+    fn seeded_is_stable() {
+        assert_eq!(4072030923, gxhash32_seeded(&[0u8; 0], 0));
+        assert_eq!(3937740989, gxhash32_seeded(&[0u8; 1], 0));
+        assert_eq!(2521723991, gxhash32_seeded(&[0u8; 1000], 0));
+        assert_eq!(3605830151, gxhash32_seeded(&[42u8; 4242], 42));
+        assert_eq!(710540313, gxhash32_seeded(&[42u8; 4242], u128::MAX));
+        assert_eq!(1416419923, gxhash32_seeded(b"Hello World", 1 << 127));
+    }
 }
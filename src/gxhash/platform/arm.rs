@@ -1,6 +1,3 @@
-#[cfg(not(any(all(target_feature = "aes", target_feature = "neon"), docsrs)))] // docs.rs bypasses the target_feature check
-compile_error!{"Gxhash requires aes and neon intrinsics. Make sure the processor supports it and build with RUSTFLAGS=\"-C target-cpu=native\" or RUSTFLAGS=\"-C target-feature=+aes,+neon\"."}
-
 #[cfg(target_arch = "arm")]
 use core::arch::arm::*;
 #[cfg(target_arch = "aarch64")]
@@ -36,6 +33,7 @@ pub unsafe fn get_partial_safe(data: *const State, len: usize) -> State {
     vaddq_s8(partial_vector, vdupq_n_s8(len as i8))
 }
 
+#[cfg(not(any(feature = "safe-reads", miri)))]
 #[inline(always)]
 pub unsafe fn get_partial_unsafe(data: *const State, len: usize) -> State {
     // May read out-of-bound, BUT we use inline assembly to ensure we can control the behavior
@@ -49,9 +47,16 @@ pub unsafe fn get_partial_unsafe(data: *const State, len: usize) -> State {
     vaddq_s8(partial_vector, len_vec)
 }
 
+// Inline assembly is not supported by Miri, and sanitizers flag reads beyond the input
+#[cfg(any(feature = "safe-reads", miri))]
+#[inline(always)]
+pub unsafe fn get_partial_unsafe(data: *const State, len: usize) -> State {
+    get_partial_safe(data, len)
+}
+
 #[inline(always)]
 // See https://blog.michaelbrase.com/2018/05/08/emulating-x86-aes-intrinsics-on-armv8-a
-pub unsafe fn aes_encrypt(data: State, keys: State) -> State {
+unsafe fn aes_encrypt(data: State, keys: State) -> State {
     // Encrypt
     let encrypted = vaeseq_u8(vreinterpretq_u8_s8(data), vdupq_n_u8(0));
     // Mix columns
@@ -62,7 +67,7 @@ pub unsafe fn aes_encrypt(data: State, keys: State) -> State {
 
 #[inline(always)]
 // See https://blog.michaelbrase.com/2018/05/08/emulating-x86-aes-intrinsics-on-armv8-a
-pub unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
+unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
     // Encrypt
     let encrypted = vaeseq_u8(vreinterpretq_u8_s8(data), vdupq_n_u8(0));
     // Xor keys
@@ -75,7 +80,12 @@ pub unsafe fn ld(array: *const u32) -> State {
 }
 
 #[inline(always)]
-pub unsafe fn compress_8(mut ptr: *const State, end_address: usize, hash_vector: State, len: usize) -> State {
+pub unsafe fn add_bytes(a: State, b: State) -> State {
+    vaddq_s8(a, b)
+}
+
+#[inline(always)]
+unsafe fn compress_8(mut ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
 
     // Disambiguation vectors
     let mut t1: State = create_empty();
@@ -100,8 +110,8 @@ pub unsafe fn compress_8(mut ptr: *const State, end_address: usize, hash_vector:
         tmp1 = aes_encrypt(tmp1, v6);
         tmp2 = aes_encrypt(tmp2, v7);
 
-        t1 = vaddq_s8(t1, ld(KEYS.as_ptr()));
-        t2 = vaddq_s8(t2, ld(KEYS.as_ptr().offset(4)));
+        t1 = vaddq_s8(t1, ld(keys.as_ptr()));
+        t2 = vaddq_s8(t2, ld(keys.as_ptr().offset(4)));
 
         lane1 = aes_encrypt_last(aes_encrypt(tmp1, t1), lane1);
         lane2 = aes_encrypt_last(aes_encrypt(tmp2, t2), lane2);
@@ -110,10 +120,44 @@ pub unsafe fn compress_8(mut ptr: *const State, end_address: usize, hash_vector:
     let len_vec =  vreinterpretq_s8_u32(vdupq_n_u32(len as u32));
     lane1 = vaddq_s8(lane1, len_vec);
     lane2 = vaddq_s8(lane2, len_vec);
-    // Merge lanes
-    aes_encrypt(lane1, lane2)
+    // Lanes are merged by the caller
+    (lane1, lane2)
+}
+
+/// AES and NEON instructions, the only backend of this platform.
+pub struct Neon;
+
+impl Aes for Neon {
+    #[inline(always)]
+    unsafe fn aes_encrypt(data: State, keys: State) -> State {
+        aes_encrypt(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
+        aes_encrypt_last(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
+        compress_8(ptr, end_address, hash_vector, len, keys)
+    }
 }
 
+#[inline(always)]
+pub fn active_backend() -> Backend {
+    Backend::Neon
+}
+
+// The backend is known at compile time
+macro_rules! dispatch {
+    ($B:ident => $body:expr) => {
+        $crate::gxhash::platform::with_static(|| { type $B = $crate::gxhash::platform::Neon; $body })
+    };
+}
+
+pub(crate) use dispatch;
+
 #[inline(always)]
 pub unsafe fn load_u8(x: u8) -> State {
     vreinterpretq_s8_u8(vdupq_n_u8(x))
@@ -1,19 +1,60 @@
-#[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+#[cfg(all(any(target_arch = "arm", target_arch = "aarch64"), target_feature = "aes", target_feature = "neon", not(feature = "force-portable")))]
 #[path = "arm.rs"]
+#[allow(clippy::module_inception)]
 mod platform;
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+// With std, AES is detected at run time when it is not enabled at compile time
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"), not(feature = "force-portable")))]
 #[path = "x86.rs"]
+#[allow(clippy::module_inception)]
 mod platform;
 
+// Fallback when AES intrinsics are not available
+#[cfg(not(all(
+    any(
+        all(any(target_arch = "arm", target_arch = "aarch64"), target_feature = "aes", target_feature = "neon"),
+        all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"))),
+    not(feature = "force-portable"))))]
+#[path = "portable.rs"]
+#[allow(clippy::module_inception)]
+mod platform;
+
+// Also built alongside the x86 backend, as a run time fallback and for testing it against AES-NI
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"), not(feature = "force-portable"), any(test, feature = "std")))]
+#[allow(dead_code, unused_imports, unused_macros)]
+mod portable;
+
+#[cfg(all(feature = "hybrid", not(feature = "std"), not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes", target_feature = "avx2", target_feature = "vaes"))))]
+compile_error!{"Hybrid feature without std is only available on x86 processors with aes, avx2 and vaes intrinsics."}
+
 pub use platform::*;
 
+use super::{Backend, Profile};
 use core::mem::size_of;
 
+/// AES rounds and the high ILP compression loop, the only operations that depend on CPU features.
+///
+/// Hashing functions are generic over it, so that they are compiled once for each backend the CPU may support.
+/// Public entry points pick the backend once per call with `dispatch!`, rather than on every round.
+pub trait Aes {
+    unsafe fn aes_encrypt(data: State, keys: State) -> State;
+    unsafe fn aes_encrypt_last(data: State, keys: State) -> State;
+    unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State);
+}
+
+/// Runs `f` with the backend known at compile time, for the `dispatch!` macro of platforms that have a single backend.
+#[inline(always)]
+#[allow(dead_code)]
+pub fn with_static<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
 pub(crate) const VECTOR_SIZE: usize = size_of::<State>();
 // 4KiB is the default page size for most systems, and conservative for other systems such as macOS ARM (16KiB)
+#[cfg(not(any(feature = "safe-reads", miri)))]
 const PAGE_SIZE: usize = 0x1000;
 
+#[cfg(not(any(feature = "safe-reads", miri)))]
 #[inline(always)]
 pub unsafe fn get_partial(p: *const State, len: usize) -> State {
     // Safety check
@@ -24,6 +65,14 @@ pub unsafe fn get_partial(p: *const State, len: usize) -> State {
     }
 }
 
+// Never reads beyond the input, for sanitizers and Miri. Hashes are the same.
+#[cfg(any(feature = "safe-reads", miri))]
+#[inline(always)]
+pub unsafe fn get_partial(p: *const State, len: usize) -> State {
+    get_partial_safe(p, len)
+}
+
+#[cfg(not(any(feature = "safe-reads", miri)))]
 #[inline(always)]
 unsafe fn check_same_page(ptr: *const State) -> bool {
     let address = ptr as usize;
@@ -34,15 +83,48 @@ unsafe fn check_same_page(ptr: *const State) -> bool {
 }
 
 #[inline(always)]
-pub unsafe fn finalize(hash: State) -> State {
-    let mut hash = aes_encrypt(hash, ld(KEYS.as_ptr()));
-    hash = aes_encrypt(hash, ld(KEYS.as_ptr().offset(4)));
-    hash = aes_encrypt_last(hash, ld(KEYS.as_ptr().offset(8)));
+pub unsafe fn finalize<B: Aes>(hash: State) -> State {
+    finalize_with::<B>(hash, &KEYS, Profile::Default)
+}
+
+#[inline(always)]
+pub unsafe fn finalize_with<B: Aes>(hash: State, keys: &[u32; 12], profile: Profile) -> State {
+    let mut hash = B::aes_encrypt(hash, ld(keys.as_ptr()));
+    match profile {
+        Profile::Fast => {
+            // Hashes enter the finalization through an aes_encrypt_last, which has no MixColumns: two full rounds
+            // are needed for every byte to affect every other. Keyed apart from the default profile's second round
+            hash = B::aes_encrypt(hash, ld(keys.as_ptr().offset(8)));
+        }
+        Profile::Default => {
+            hash = B::aes_encrypt(hash, ld(keys.as_ptr().offset(4)));
+        }
+        Profile::Hardened => {
+            hash = B::aes_encrypt(hash, ld(keys.as_ptr().offset(4)));
+            hash = B::aes_encrypt(hash, ld(keys.as_ptr().offset(8)));
+            hash = B::aes_encrypt(hash, ld(keys.as_ptr()));
+            hash = B::aes_encrypt(hash, ld(keys.as_ptr().offset(4)));
+        }
+    }
+    hash = B::aes_encrypt_last(hash, ld(keys.as_ptr().offset(8)));
 
     hash
 }
 
+/// Expands a 128-bit seed into a full-entropy state through AES rounds, so that both halves of
+/// the state depend on the whole seed.
+#[inline(always)]
+pub unsafe fn create_seed128<B: Aes>(seed: u128) -> State {
+    finalize::<B>(B::aes_encrypt(load_u128(seed), ld(KEYS.as_ptr().offset(8))))
+}
+
 pub const KEYS: [u32; 12] = 
    [0xF2784542, 0xB09D3E21, 0x89C222E5, 0xFC3BC28E,
     0x03FCE279, 0xCB6B2E9B, 0xB361DC58, 0x39132BD9,
-    0xD0012E32, 0x689D2B7D, 0x5544B1B7, 0xC78B122B];
\ No newline at end of file
+    0xD0012E32, 0x689D2B7D, 0x5544B1B7, 0xC78B122B];
+
+/// Round keys for the second half of 256-bit hashes, for domain separation with [`KEYS`]
+pub const KEYS_256: [u32; 12] = 
+   [0x428A2F98, 0x71374491, 0xB5C0FBCF, 0xE9B5DBA5,
+    0x3956C25B, 0x59F111F1, 0x923F82A4, 0xAB1C5ED5,
+    0xD807AA98, 0x12835B01, 0x243185BE, 0x550C7DC3];
\ No newline at end of file
@@ -0,0 +1,367 @@
+// Pure-Rust fallback for platforms without AES intrinsics (or with the `force-portable` feature).
+// AES rounds are emulated byte by byte, with the same semantics and memory layout as the x86 intrinsics,
+// so that hashes are identical to the SIMD backends. The state holds bytes in little-endian order whatever the
+// target: round keys and integers are loaded as little-endian, and hashes are read back as little-endian.
+// Note that the S-box lookups are not constant-time.
+
+use super::*;
+
+#[derive(Clone, Copy, Debug)]
+#[repr(C, align(16))]
+pub struct State([u8; 16]);
+
+#[inline(always)]
+pub unsafe fn create_empty() -> State {
+    State([0; 16])
+}
+
+#[inline(always)]
+pub unsafe fn create_seed(seed: i64) -> State {
+    load_i64(seed)
+}
+
+#[inline(always)]
+pub unsafe fn load_unaligned(p: *const State) -> State {
+    core::ptr::read_unaligned(p)
+}
+
+#[inline(always)]
+pub unsafe fn get_partial_safe(data: *const State, len: usize) -> State {
+    // Temporary buffer filled with zeros
+    let mut buffer = [0u8; VECTOR_SIZE];
+    // Copy data into the buffer
+    core::ptr::copy(data as *const u8, buffer.as_mut_ptr(), len);
+    add_bytes(State(buffer), load_u8(len as u8))
+}
+
+#[inline(always)]
+pub unsafe fn get_partial_unsafe(data: *const State, len: usize) -> State {
+    // Reading beyond the input is only done through inline assembly on SIMD backends
+    get_partial_safe(data, len)
+}
+
+#[inline(always)]
+pub(super) unsafe fn aes_encrypt(data: State, keys: State) -> State {
+    xor(mix_columns(shift_rows(sub_bytes(data))), keys)
+}
+
+#[inline(always)]
+pub(super) unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
+    xor(shift_rows(sub_bytes(data)), keys)
+}
+
+#[inline(always)]
+pub unsafe fn ld(array: *const u32) -> State {
+    let words = core::ptr::read_unaligned(array as *const [u32; 4]);
+    let mut state = [0u8; 16];
+    for (chunk, word) in state.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    State(state)
+}
+
+#[inline(always)]
+pub unsafe fn add_bytes(a: State, b: State) -> State {
+    let mut result = a;
+    for (r, b) in result.0.iter_mut().zip(b.0) {
+        *r = r.wrapping_add(b);
+    }
+    result
+}
+
+#[inline(always)]
+pub(super) unsafe fn compress_8(mut ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
+
+    // Disambiguation vectors
+    let mut t1: State = create_empty();
+    let mut t2: State = create_empty();
+
+    // Hash is processed in two separate 128-bit parallel lanes, as on SIMD backends
+    let mut lane1 = hash_vector;
+    let mut lane2 = hash_vector;
+
+    while (ptr as usize) < end_address {
+
+        crate::gxhash::load_unaligned!(ptr, v0, v1, v2, v3, v4, v5, v6, v7);
+
+        let mut tmp1 = aes_encrypt(v0, v2);
+        let mut tmp2 = aes_encrypt(v1, v3);
+
+        tmp1 = aes_encrypt(tmp1, v4);
+        tmp2 = aes_encrypt(tmp2, v5);
+
+        tmp1 = aes_encrypt(tmp1, v6);
+        tmp2 = aes_encrypt(tmp2, v7);
+
+        t1 = add_bytes(t1, ld(keys.as_ptr()));
+        t2 = add_bytes(t2, ld(keys.as_ptr().offset(4)));
+
+        lane1 = aes_encrypt_last(aes_encrypt(tmp1, t1), lane1);
+        lane2 = aes_encrypt_last(aes_encrypt(tmp2, t2), lane2);
+    }
+    // For 'Zeroes' test
+    let len_vec = load_u32(len as u32);
+    lane1 = add_bytes(lane1, len_vec);
+    lane2 = add_bytes(lane2, len_vec);
+    // Lanes are merged by the caller
+    (lane1, lane2)
+}
+
+// The items below are not built when this file is built alongside the x86 backend, whose `State` is another type
+
+/// Software AES, the only backend of this platform.
+#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"), not(feature = "force-portable"))))]
+pub struct Software;
+
+#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"), not(feature = "force-portable"))))]
+impl Aes for Software {
+    #[inline(always)]
+    unsafe fn aes_encrypt(data: State, keys: State) -> State {
+        aes_encrypt(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
+        aes_encrypt_last(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
+        compress_8(ptr, end_address, hash_vector, len, keys)
+    }
+}
+
+#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"), not(feature = "force-portable"))))]
+#[inline(always)]
+pub fn active_backend() -> Backend {
+    Backend::Portable
+}
+
+// The backend is known at compile time
+#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"), not(feature = "force-portable"))))]
+macro_rules! dispatch {
+    ($B:ident => $body:expr) => {
+        $crate::gxhash::platform::with_static(|| { type $B = $crate::gxhash::platform::Software; $body })
+    };
+}
+
+#[cfg(not(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2", any(target_feature = "aes", feature = "std"), not(feature = "force-portable"))))]
+pub(crate) use dispatch;
+
+#[inline(always)]
+pub unsafe fn load_u8(x: u8) -> State {
+    State([x; 16])
+}
+
+#[inline(always)]
+pub unsafe fn load_u16(x: u16) -> State {
+    splat(&x.to_le_bytes())
+}
+
+#[inline(always)]
+pub unsafe fn load_u32(x: u32) -> State {
+    splat(&x.to_le_bytes())
+}
+
+#[inline(always)]
+pub unsafe fn load_u64(x: u64) -> State {
+    splat(&x.to_le_bytes())
+}
+
+#[inline(always)]
+pub unsafe fn load_u128(x: u128) -> State {
+    State(x.to_le_bytes())
+}
+
+#[inline(always)]
+pub unsafe fn load_i8(x: i8) -> State {
+    load_u8(x as u8)
+}
+
+#[inline(always)]
+pub unsafe fn load_i16(x: i16) -> State {
+    load_u16(x as u16)
+}
+
+#[inline(always)]
+pub unsafe fn load_i32(x: i32) -> State {
+    load_u32(x as u32)
+}
+
+#[inline(always)]
+pub unsafe fn load_i64(x: i64) -> State {
+    load_u64(x as u64)
+}
+
+#[inline(always)]
+pub unsafe fn load_i128(x: i128) -> State {
+    load_u128(x as u128)
+}
+
+#[inline(always)]
+fn splat(bytes: &[u8]) -> State {
+    let mut state = [0u8; 16];
+    for chunk in state.chunks_exact_mut(bytes.len()) {
+        chunk.copy_from_slice(bytes);
+    }
+    State(state)
+}
+
+#[inline(always)]
+fn xor(a: State, b: State) -> State {
+    let mut result = a;
+    for (r, b) in result.0.iter_mut().zip(b.0) {
+        *r ^= b;
+    }
+    result
+}
+
+#[inline(always)]
+fn sub_bytes(state: State) -> State {
+    State(state.0.map(|b| SBOX[b as usize]))
+}
+
+// The state is stored column by column: byte `row + 4 * column`. Row `r` is rotated left by `r` columns.
+#[inline(always)]
+fn shift_rows(state: State) -> State {
+    let s = state.0;
+    let mut result = [0u8; 16];
+    for (i, r) in result.iter_mut().enumerate() {
+        let (row, column) = (i % 4, i / 4);
+        *r = s[row + 4 * ((column + row) % 4)];
+    }
+    State(result)
+}
+
+#[inline(always)]
+fn mix_columns(state: State) -> State {
+    let mut result = state.0;
+    for column in result.chunks_exact_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        let all = a0 ^ a1 ^ a2 ^ a3;
+        // 2a ^ 3b ^ c ^ d == a ^ all ^ 2(a ^ b)
+        column[0] = a0 ^ all ^ xtime(a0 ^ a1);
+        column[1] = a1 ^ all ^ xtime(a1 ^ a2);
+        column[2] = a2 ^ all ^ xtime(a2 ^ a3);
+        column[3] = a3 ^ all ^ xtime(a3 ^ a0);
+    }
+    State(result)
+}
+
+// Multiplication by 2 in GF(2^8)
+#[inline(always)]
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ ((b >> 7) * 0x1B)
+}
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7C, 0x77, 0x7B, 0xF2, 0x6B, 0x6F, 0xC5, 0x30, 0x01, 0x67, 0x2B, 0xFE, 0xD7, 0xAB, 0x76,
+    0xCA, 0x82, 0xC9, 0x7D, 0xFA, 0x59, 0x47, 0xF0, 0xAD, 0xD4, 0xA2, 0xAF, 0x9C, 0xA4, 0x72, 0xC0,
+    0xB7, 0xFD, 0x93, 0x26, 0x36, 0x3F, 0xF7, 0xCC, 0x34, 0xA5, 0xE5, 0xF1, 0x71, 0xD8, 0x31, 0x15,
+    0x04, 0xC7, 0x23, 0xC3, 0x18, 0x96, 0x05, 0x9A, 0x07, 0x12, 0x80, 0xE2, 0xEB, 0x27, 0xB2, 0x75,
+    0x09, 0x83, 0x2C, 0x1A, 0x1B, 0x6E, 0x5A, 0xA0, 0x52, 0x3B, 0xD6, 0xB3, 0x29, 0xE3, 0x2F, 0x84,
+    0x53, 0xD1, 0x00, 0xED, 0x20, 0xFC, 0xB1, 0x5B, 0x6A, 0xCB, 0xBE, 0x39, 0x4A, 0x4C, 0x58, 0xCF,
+    0xD0, 0xEF, 0xAA, 0xFB, 0x43, 0x4D, 0x33, 0x85, 0x45, 0xF9, 0x02, 0x7F, 0x50, 0x3C, 0x9F, 0xA8,
+    0x51, 0xA3, 0x40, 0x8F, 0x92, 0x9D, 0x38, 0xF5, 0xBC, 0xB6, 0xDA, 0x21, 0x10, 0xFF, 0xF3, 0xD2,
+    0xCD, 0x0C, 0x13, 0xEC, 0x5F, 0x97, 0x44, 0x17, 0xC4, 0xA7, 0x7E, 0x3D, 0x64, 0x5D, 0x19, 0x73,
+    0x60, 0x81, 0x4F, 0xDC, 0x22, 0x2A, 0x90, 0x88, 0x46, 0xEE, 0xB8, 0x14, 0xDE, 0x5E, 0x0B, 0xDB,
+    0xE0, 0x32, 0x3A, 0x0A, 0x49, 0x06, 0x24, 0x5C, 0xC2, 0xD3, 0xAC, 0x62, 0x91, 0x95, 0xE4, 0x79,
+    0xE7, 0xC8, 0x37, 0x6D, 0x8D, 0xD5, 0x4E, 0xA9, 0x6C, 0x56, 0xF4, 0xEA, 0x65, 0x7A, 0xAE, 0x08,
+    0xBA, 0x78, 0x25, 0x2E, 0x1C, 0xA6, 0xB4, 0xC6, 0xE8, 0xDD, 0x74, 0x1F, 0x4B, 0xBD, 0x8B, 0x8A,
+    0x70, 0x3E, 0xB5, 0x66, 0x48, 0x03, 0xF6, 0x0E, 0x61, 0x35, 0x57, 0xB9, 0x86, 0xC1, 0x1D, 0x9E,
+    0xE1, 0xF8, 0x98, 0x11, 0x69, 0xD9, 0x8E, 0x94, 0x9B, 0x1E, 0x87, 0xE9, 0xCE, 0x55, 0x28, 0xDF,
+    0x8C, 0xA1, 0x89, 0x0D, 0xBF, 0xE6, 0x42, 0x68, 0x41, 0x99, 0x2D, 0x0F, 0xB0, 0x54, 0xBB, 0x16,
+];
+
+#[cfg(all(test, any(target_arch = "x86", target_arch = "x86_64"), target_feature = "aes"))]
+mod tests {
+
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    use super::*;
+    use rand::Rng;
+
+    fn random_state() -> State {
+        State(rand::thread_rng().gen())
+    }
+
+    unsafe fn to_m128i(state: State) -> __m128i {
+        _mm_loadu_si128(state.0.as_ptr() as *const __m128i)
+    }
+
+    unsafe fn from_m128i(vector: __m128i) -> State {
+        let mut state = create_empty();
+        _mm_storeu_si128(state.0.as_mut_ptr() as *mut __m128i, vector);
+        state
+    }
+
+    #[test]
+    fn aes_matches_intrinsics() {
+        for _ in 0..10_000 {
+            let (data, keys) = (random_state(), random_state());
+            unsafe {
+                assert_eq!(from_m128i(_mm_aesenc_si128(to_m128i(data), to_m128i(keys))).0, aes_encrypt(data, keys).0);
+                assert_eq!(from_m128i(_mm_aesenclast_si128(to_m128i(data), to_m128i(keys))).0, aes_encrypt_last(data, keys).0);
+            }
+        }
+    }
+
+    #[test]
+    fn loads_match_intrinsics() {
+        let x: u128 = rand::thread_rng().gen();
+        unsafe {
+            assert_eq!(from_m128i(_mm_set1_epi8(x as i8)).0, load_u8(x as u8).0);
+            assert_eq!(from_m128i(_mm_set1_epi16(x as i16)).0, load_u16(x as u16).0);
+            assert_eq!(from_m128i(_mm_set1_epi32(x as i32)).0, load_u32(x as u32).0);
+            assert_eq!(from_m128i(_mm_set1_epi64x(x as i64)).0, load_u64(x as u64).0);
+            assert_eq!(from_m128i(_mm_loadu_si128(&x as *const u128 as *const __m128i)).0, load_u128(x).0);
+            let (a, b) = (random_state(), random_state());
+            assert_eq!(from_m128i(_mm_add_epi8(to_m128i(a), to_m128i(b))).0, add_bytes(a, b).0);
+        }
+    }
+
+    // With `force-portable`, this is the only backend
+    #[cfg(not(feature = "force-portable"))]
+    #[test]
+    fn compress_8_matches_simd_backend() {
+        let mut bytes = vec![0u8; 4096];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+        let keys = crate::gxhash::platform::KEYS;
+        for len in (0..=bytes.len()).step_by(128) {
+            unsafe {
+                let ptr = bytes.as_ptr();
+                let end = ptr as usize + len;
+                let hash_vector = random_state();
+                let (lane1, lane2) = compress_8(ptr as *const State, end, hash_vector, len, &keys);
+                let (simd1, simd2) = <super::super::AesNi as Aes>::compress_8(ptr as *const __m128i, end, to_m128i(hash_vector), len, &keys);
+                assert_eq!(from_m128i(simd1).0, lane1.0, "Lane 1 mismatch for input of size {len}");
+                assert_eq!(from_m128i(simd2).0, lane2.0, "Lane 2 mismatch for input of size {len}");
+            }
+        }
+    }
+}
+
+// Same values as with the SIMD backends. Round keys, integers and hashes go through little-endian conversions,
+// which are only exercised on big-endian targets.
+#[cfg(all(test, feature = "force-portable"))]
+mod known_vectors {
+
+    use core::hash::BuildHasher;
+
+    use crate::*;
+
+    #[test]
+    fn hashes_match_simd_backends() {
+        assert_eq!(1156095992, gxhash32(b"Hello World", i64::MAX));
+        assert_eq!(15379333799524352691, gxhash64(b"Hello World", 42));
+        assert_eq!(0x796d51ef2086c56783a452ff55382faa, gxhash128(&[42u8; 1000], 1234));
+        assert_eq!(0xf6ab102dcd09b3553008fdcdccd51b94, gxhash128_seeded(b"Hello World", 0x0123456789abcdef_fedcba9876543210));
+        assert_eq!(0x7cf8d061966f45a923dc86057706a282, GxBuildHasher::with_seed128(0x0123456789abcdef_fedcba9876543210).seed());
+        assert_eq!(2903811332116670252, GxBuildHasher::with_seed(42).hash_one(0x0123456789abcdefu64));
+        let secret = GxSecret::from_key128(0x0123456789abcdef_fedcba9876543210);
+        assert_eq!(5793367560621392303, gxhash64_keyed(b"Hello World", 42, &secret));
+    }
+}
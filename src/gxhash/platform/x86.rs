@@ -1,9 +1,3 @@
-#[cfg(not(any(all(target_feature = "aes", target_feature = "sse2"), docsrs, doc)))] // docs.rs bypasses the target_feature check
-compile_error!{"Gxhash requires aes and sse2 intrinsics. Make sure the processor supports it and build with RUSTFLAGS=\"-C target-cpu=native\" or RUSTFLAGS=\"-C target-feature=+aes,+sse2\"."}
-
-#[cfg(all(feature = "hybrid", not(all(target_feature = "aes", target_feature = "avx2"))))]
-compile_error!{"Hybrid feature is only available on x86 processors with aes and avx2 intrinsics."}
-
 #[cfg(target_arch = "x86")]
 use core::arch::x86::*;
 #[cfg(target_arch = "x86_64")]
@@ -39,6 +33,7 @@ pub unsafe fn get_partial_safe(data: *const State, len: usize) -> State {
     _mm_add_epi8(partial_vector, _mm_set1_epi8(len as i8))
 }
 
+#[cfg(not(any(feature = "safe-reads", miri)))]
 #[inline(always)]
 pub unsafe fn get_partial_unsafe(data: *const State, len: usize) -> State {
     // May read out-of-bound, BUT we use inline assembly to ensure we can control the behavior
@@ -52,27 +47,40 @@ pub unsafe fn get_partial_unsafe(data: *const State, len: usize) -> State {
     _mm_add_epi8(partial_vector, len_vec)
 }
 
+// Inline assembly is not supported by Miri, and sanitizers flag reads beyond the input
+#[cfg(any(feature = "safe-reads", miri))]
 #[inline(always)]
-#[allow(dead_code)]
-pub unsafe fn aes_encrypt(data: State, keys: State) -> State {
-    _mm_aesenc_si128(data, keys)
+pub unsafe fn get_partial_unsafe(data: *const State, len: usize) -> State {
+    get_partial_safe(data, len)
 }
 
 #[inline(always)]
 #[allow(dead_code)]
-pub unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
-    _mm_aesenclast_si128(data, keys)
+pub unsafe fn ld(array: *const u32) -> State {
+    _mm_loadu_si128(array as *const State)
 }
 
+// Both states have the same memory layout
+#[cfg(all(feature = "std", not(target_feature = "aes")))]
 #[inline(always)]
-#[allow(dead_code)]
-pub unsafe fn ld(array: *const u32) -> State {
-    _mm_loadu_si128(array as *const State)
+unsafe fn to_portable(state: State) -> super::portable::State {
+    core::mem::transmute::<State, super::portable::State>(state)
+}
+
+#[cfg(all(feature = "std", not(target_feature = "aes")))]
+#[inline(always)]
+unsafe fn from_portable(state: super::portable::State) -> State {
+    core::mem::transmute::<super::portable::State, State>(state)
 }
 
-#[cfg(not(feature = "hybrid"))]
 #[inline(always)]
-pub unsafe fn compress_8(mut ptr: *const State, end_address: usize, hash_vector: State, len: usize) -> State {
+pub unsafe fn add_bytes(a: State, b: State) -> State {
+    _mm_add_epi8(a, b)
+}
+
+#[inline(always)]
+#[allow(dead_code)]
+unsafe fn compress_8_128(mut ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
 
     // Disambiguation vectors
     let mut t1: State = create_empty();
@@ -88,32 +96,32 @@ pub unsafe fn compress_8(mut ptr: *const State, end_address: usize, hash_vector:
 
         crate::gxhash::load_unaligned!(ptr, v0, v1, v2, v3, v4, v5, v6, v7);
 
-        let mut tmp1 = aes_encrypt(v0, v2);
-        let mut tmp2 = aes_encrypt(v1, v3);
+        let mut tmp1 = _mm_aesenc_si128(v0, v2);
+        let mut tmp2 = _mm_aesenc_si128(v1, v3);
 
-        tmp1 = aes_encrypt(tmp1, v4);
-        tmp2 = aes_encrypt(tmp2, v5);
+        tmp1 = _mm_aesenc_si128(tmp1, v4);
+        tmp2 = _mm_aesenc_si128(tmp2, v5);
 
-        tmp1 = aes_encrypt(tmp1, v6);
-        tmp2 = aes_encrypt(tmp2, v7);
+        tmp1 = _mm_aesenc_si128(tmp1, v6);
+        tmp2 = _mm_aesenc_si128(tmp2, v7);
 
-        t1 = _mm_add_epi8(t1, ld(KEYS.as_ptr()));
-        t2 = _mm_add_epi8(t2, ld(KEYS.as_ptr().offset(4)));
+        t1 = _mm_add_epi8(t1, ld(keys.as_ptr()));
+        t2 = _mm_add_epi8(t2, ld(keys.as_ptr().offset(4)));
 
-        lane1 = aes_encrypt_last(aes_encrypt(tmp1, t1), lane1);
-        lane2 = aes_encrypt_last(aes_encrypt(tmp2, t2), lane2);
+        lane1 = _mm_aesenclast_si128(_mm_aesenc_si128(tmp1, t1), lane1);
+        lane2 = _mm_aesenclast_si128(_mm_aesenc_si128(tmp2, t2), lane2);
     }
     // For 'Zeroes' test
     let len_vec =  _mm_set1_epi32(len as i32);
     lane1 = _mm_add_epi8(lane1, len_vec);
     lane2 = _mm_add_epi8(lane2, len_vec);
-    // Merge lanes
-    aes_encrypt(lane1, lane2)
+    // Lanes are merged by the caller
+    (lane1, lane2)
 }
 
 #[cfg(feature = "hybrid")]
 #[inline(always)]
-pub unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: State, len: usize) -> State {
+unsafe fn compress_8_256(ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
     macro_rules! load_unaligned_x2 {
         ($ptr:ident, $($var:ident),+) => {
             $(
@@ -135,7 +143,7 @@ pub unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: Sta
         tmp = _mm256_aesenc_epi128(tmp, v2);
         tmp = _mm256_aesenc_epi128(tmp, v3);
 
-        t = _mm256_add_epi8(t, _mm256_loadu_si256(KEYS.as_ptr() as *const __m256i));
+        t = _mm256_add_epi8(t, _mm256_loadu_si256(keys.as_ptr() as *const __m256i));
 
         lane = _mm256_aesenclast_epi128(_mm256_aesenc_epi128(tmp, t), lane);
     }
@@ -146,8 +154,211 @@ pub unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: Sta
     let len_vec =  _mm_set1_epi32(len as i32);
     lane1 = _mm_add_epi8(lane1, len_vec);
     lane2 = _mm_add_epi8(lane2, len_vec);
-    // Merge lanes
-    aes_encrypt(lane1, lane2)
+    // Lanes are merged by the caller
+    (lane1, lane2)
+}
+
+/// 128-bit AES-NI instructions.
+#[allow(dead_code)]
+pub struct AesNi;
+
+impl Aes for AesNi {
+    #[inline(always)]
+    unsafe fn aes_encrypt(data: State, keys: State) -> State {
+        _mm_aesenc_si128(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
+        _mm_aesenclast_si128(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
+        compress_8_128(ptr, end_address, hash_vector, len, keys)
+    }
+}
+
+/// 128-bit AES-NI instructions, and 256-bit VAES instructions for the high ILP loop. Hashes are the same as with [`AesNi`].
+#[cfg(feature = "hybrid")]
+pub struct Vaes;
+
+#[cfg(feature = "hybrid")]
+impl Aes for Vaes {
+    #[inline(always)]
+    unsafe fn aes_encrypt(data: State, keys: State) -> State {
+        _mm_aesenc_si128(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
+        _mm_aesenclast_si128(data, keys)
+    }
+
+    #[inline(always)]
+    unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
+        compress_8_256(ptr, end_address, hash_vector, len, keys)
+    }
+}
+
+/// Software AES, for CPUs without AES-NI.
+#[cfg(all(feature = "std", not(target_feature = "aes")))]
+pub struct Software;
+
+#[cfg(all(feature = "std", not(target_feature = "aes")))]
+impl Aes for Software {
+    #[inline(always)]
+    unsafe fn aes_encrypt(data: State, keys: State) -> State {
+        from_portable(super::portable::aes_encrypt(to_portable(data), to_portable(keys)))
+    }
+
+    #[inline(always)]
+    unsafe fn aes_encrypt_last(data: State, keys: State) -> State {
+        from_portable(super::portable::aes_encrypt_last(to_portable(data), to_portable(keys)))
+    }
+
+    #[inline(always)]
+    unsafe fn compress_8(ptr: *const State, end_address: usize, hash_vector: State, len: usize, keys: &[u32; 12]) -> (State, State) {
+        let (lane1, lane2) = super::portable::compress_8(ptr as *const _, end_address, to_portable(hash_vector), len, keys);
+        (from_portable(lane1), from_portable(lane2))
+    }
+}
+
+// Without std, or with AES enabled at compile time and VAES not compiled in, the backend is known at compile time
+#[cfg(all(not(all(feature = "std", any(not(target_feature = "aes"), feature = "hybrid"))), feature = "hybrid"))]
+pub type StaticBackend = Vaes;
+
+#[cfg(all(not(all(feature = "std", any(not(target_feature = "aes"), feature = "hybrid"))), not(feature = "hybrid")))]
+pub type StaticBackend = AesNi;
+
+#[cfg(not(all(feature = "std", any(not(target_feature = "aes"), feature = "hybrid"))))]
+#[inline(always)]
+pub fn active_backend() -> Backend {
+    if cfg!(feature = "hybrid") { Backend::Vaes } else { Backend::Aes }
+}
+
+#[cfg(not(all(feature = "std", any(not(target_feature = "aes"), feature = "hybrid"))))]
+macro_rules! dispatch {
+    ($B:ident => $body:expr) => {
+        $crate::gxhash::platform::with_static(|| { type $B = $crate::gxhash::platform::StaticBackend; $body })
+    };
+}
+
+// Otherwise, CPU features are detected at the first call, and each entry point runs the copy of its body compiled
+// for the detected backend. Copies for AES-NI and VAES run in `target_feature` functions, so that intrinsics are inlined.
+#[cfg(all(feature = "std", any(not(target_feature = "aes"), feature = "hybrid")))]
+macro_rules! dispatch {
+    ($B:ident => $body:expr) => {
+        match $crate::gxhash::platform::active_backend() {
+            #[cfg(feature = "hybrid")]
+            $crate::Backend::Vaes => {
+                let f = || { type $B = $crate::gxhash::platform::Vaes; $body };
+                unsafe { $crate::gxhash::platform::with_vaes(f) }
+            },
+            #[cfg(not(target_feature = "aes"))]
+            $crate::Backend::Portable => {
+                $crate::gxhash::platform::with_static(|| { type $B = $crate::gxhash::platform::Software; $body })
+            },
+            _ => {
+                let f = || { type $B = $crate::gxhash::platform::AesNi; $body };
+                unsafe { $crate::gxhash::platform::with_aes(f) }
+            },
+        }
+    };
+}
+
+pub(crate) use dispatch;
+
+#[cfg(all(feature = "std", any(not(target_feature = "aes"), feature = "hybrid")))]
+pub use detection::*;
+
+#[cfg(all(feature = "std", any(not(target_feature = "aes"), feature = "hybrid")))]
+mod detection {
+    use super::*;
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    // Detected backend, cached after the first call (0 until then)
+    static BACKEND: AtomicU8 = AtomicU8::new(0);
+    const VAES: u8 = 1;
+    const AES: u8 = 2;
+    const PORTABLE: u8 = 3;
+
+    #[inline(always)]
+    pub fn active_backend() -> Backend {
+        match BACKEND.load(Ordering::Relaxed) {
+            VAES => Backend::Vaes,
+            AES => Backend::Aes,
+            PORTABLE => Backend::Portable,
+            _ => detect(),
+        }
+    }
+
+    #[cold]
+    fn detect() -> Backend {
+        #[cfg(feature = "hybrid")]
+        if is_x86_feature_detected!("aes") && is_x86_feature_detected!("avx2") && is_x86_feature_detected!("vaes") {
+            BACKEND.store(VAES, Ordering::Relaxed);
+            return Backend::Vaes;
+        }
+        if is_x86_feature_detected!("aes") {
+            BACKEND.store(AES, Ordering::Relaxed);
+            Backend::Aes
+        } else {
+            BACKEND.store(PORTABLE, Ordering::Relaxed);
+            Backend::Portable
+        }
+    }
+
+    /// Runs `f` with AES-NI enabled.
+    ///
+    /// # Safety
+    /// The CPU must support AES-NI.
+    #[target_feature(enable = "aes")]
+    #[inline]
+    pub unsafe fn with_aes<R>(f: impl FnOnce() -> R) -> R {
+        f()
+    }
+
+    /// Runs `f` with AES-NI, AVX2 and VAES enabled.
+    ///
+    /// # Safety
+    /// The CPU must support AES-NI, AVX2 and VAES.
+    #[cfg(feature = "hybrid")]
+    #[target_feature(enable = "aes,avx2,vaes")]
+    #[inline]
+    pub unsafe fn with_vaes<R>(f: impl FnOnce() -> R) -> R {
+        f()
+    }
+
+    #[cfg(all(test, feature = "hybrid"))]
+    mod tests {
+
+        use super::*;
+        use rand::Rng;
+
+        #[test]
+        fn vaes_matches_aes() {
+            // The CPU running the tests can't be known in advance for #[ignore], so the skip is reported instead
+            if !(is_x86_feature_detected!("aes") && is_x86_feature_detected!("avx2") && is_x86_feature_detected!("vaes")) {
+                eprintln!("skipped vaes_matches_aes: this CPU doesn't support AES-NI, AVX2 and VAES");
+                return;
+            }
+            let mut bytes = vec![0u8; 8192 + 16];
+            rand::thread_rng().fill(bytes.as_mut_slice());
+            for len in 0..=8192 {
+                unsafe {
+                    // Only whole groups of 8 vectors go through compress_8, starting at any alignment
+                    let ptr = bytes.as_ptr().add(len % 16) as *const State;
+                    let end = ptr as usize + len / 128 * 128;
+                    let hash_vector = load_u64(len as u64);
+                    let (aes1, aes2) = with_aes(|| AesNi::compress_8(ptr, end, hash_vector, len, &KEYS));
+                    let (vaes1, vaes2) = with_vaes(|| Vaes::compress_8(ptr, end, hash_vector, len, &KEYS));
+                    assert_eq!(_mm_movemask_epi8(_mm_cmpeq_epi8(aes1, vaes1)), 0xFFFF, "Lane 1 mismatch for input of size {len}");
+                    assert_eq!(_mm_movemask_epi8(_mm_cmpeq_epi8(aes2, vaes2)), 0xFFFF, "Lane 2 mismatch for input of size {len}");
+                }
+            }
+        }
+    }
 }
 
 #[inline(always)]
@@ -1,5 +1,6 @@
-use std::hash::{BuildHasher, Hasher};
+use core::hash::{BuildHasher, Hash, Hasher};
 
+use crate::batch::*;
 use crate::gxhash::platform::*;
 use crate::gxhash::*;
 
@@ -7,20 +8,73 @@ use crate::gxhash::*;
 /// # Features
 /// - The fastest [`Hasher`] of its class<sup>1</sup>, for all input sizes
 /// - Highly collision resistant
-/// - DOS resistance thanks to seed randomization when using [`GxHasher::default()`] unless the
-///   "deterministic" feature is enabled.
+/// - DOS resistance thanks to seed randomization when built from [`GxBuildHasher::default()`]
 ///
 /// *<sup>1</sup>There might be faster alternatives, such as `fxhash` for very small input sizes,
 /// but that usually have low quality properties.*
 #[derive(Clone, Debug)]
 pub struct GxHasher {
     state: State,
+    pending: Pending,
+    profile: Profile,
 }
 
 impl GxHasher {
     #[inline]
     fn with_state(state: State) -> GxHasher {
-        GxHasher { state }
+        GxHasher { state, pending: Pending::default(), profile: Profile::Default }
+    }
+
+    /// The state with pending bytes mixed in, if any.
+    #[inline(always)]
+    unsafe fn flushed_state<B: Aes>(&self) -> State {
+        self.pending.mix::<B>(self.state, &KEYS)
+    }
+}
+
+/// Small integer writes of a hasher, packed to be mixed into its state together. Bytes after `len` are zero.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Pending {
+    bytes: [u8; VECTOR_SIZE],
+    len: usize,
+}
+
+impl Pending {
+    /// The state with the pending bytes mixed in with the given round keys, if any.
+    #[inline(always)]
+    pub(crate) unsafe fn mix<B: Aes>(&self, state: State, keys: &[u32; 12]) -> State {
+        if self.len == 0 {
+            return state;
+        }
+        // Same as a partial load of the pending bytes
+        let pending = add_bytes(load_unaligned(self.bytes.as_ptr() as *const State), load_u8(self.len as u8));
+        // Integers fill only part of the vector, so they get a full round of their own for avalanche. This round
+        // runs in parallel with the one on the state. Not the same round key as for slices, so that packed integers
+        // and slices of same bytes don't collide
+        let pending = B::aes_encrypt(pending, ld(keys.as_ptr().offset(8)));
+        B::aes_encrypt_last(pending, B::aes_encrypt(state, ld(keys.as_ptr().offset(4))))
+    }
+
+    /// Mixes the pending bytes into the state, if any, and clears them.
+    #[inline(always)]
+    pub(crate) fn flush(&mut self, state: &mut State, keys: &[u32; 12]) {
+        if self.len != 0 {
+            *state = dispatch!(B => unsafe { self.mix::<B>(*state, keys) });
+            *self = Pending::default();
+        }
+    }
+
+    /// Appends the bytes of an integer, first flushing the pending bytes into the state if they would not fit.
+    #[inline(always)]
+    pub(crate) fn pack<const N: usize>(&mut self, bytes: [u8; N], state: &mut State, keys: &[u32; 12]) {
+        if self.len + N > VECTOR_SIZE {
+            self.flush(state, keys);
+        }
+        // Safety: N is at most VECTOR_SIZE, so the bytes fit after flushing
+        unsafe {
+            core::ptr::copy_nonoverlapping(bytes.as_ptr(), self.bytes.as_mut_ptr().add(self.len), N);
+        }
+        self.len += N;
     }
 }
 
@@ -78,26 +132,145 @@ impl GxHasher {
         GxHasher::with_state(unsafe { create_seed(seed) })
     }
 
+    /// Creates a new hasher using the provided 128-bit seed.
+    ///
+    /// The seed is expanded through AES rounds so that all 128 bits of the internal state depend on it,
+    /// whereas [`GxHasher::with_seed`] duplicates its 64-bit seed in both halves of the state.
+    ///
+    /// # Warning ⚠️
+    /// Hardcoding a seed may make your [`Hasher`] vulnerable to DOS attacks.
+    /// It is recommended to use [`GxBuildHasher::default()`] for improved DOS resistance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use gxhash::GxHasher;
+    ///
+    /// let mut hasher = GxHasher::with_seed128(0x0123456789abcdef0123456789abcdef);
+    ///
+    /// hasher.write(b"Hello");
+    ///
+    /// println!("Hash is {:x}!", hasher.finish());
+    /// ```
+    #[inline]
+    pub fn with_seed128(seed: u128) -> GxHasher {
+        GxHasher::with_state(dispatch!(B => unsafe { create_seed128::<B>(seed) }))
+    }
+
     /// Finish this hasher and return the hashed value as a 128-bit
     /// unsigned integer.
     #[inline]
     pub fn finish_u128(&self) -> u128 {
-        debug_assert!(std::mem::size_of::<State>() >= std::mem::size_of::<u128>());
+        debug_assert!(core::mem::size_of::<State>() >= core::mem::size_of::<u128>());
 
-        unsafe {
-            let p = &finalize(self.state) as *const State as *const u128;
-            *p
+        dispatch!(B => unsafe {
+            let p = &finalize_with::<B>(self.flushed_state::<B>(), &KEYS, self.profile) as *const State as *const u128;
+            u128::from_le(*p)
+        })
+    }
+
+    /// Finish this hasher and fill the output with hash bytes, for digests of arbitrary length.
+    ///
+    /// The output is prefix-consistent: its first 16 bytes are the same as [`GxHasher::finish_u128`]
+    /// (in little-endian order). See [`gxhash_xof`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use gxhash::GxHasher;
+    ///
+    /// let mut hasher = GxHasher::with_seed(1234);
+    /// hasher.write(b"Hello");
+    ///
+    /// let mut fingerprint = [0u8; 32];
+    /// hasher.finish_into(&mut fingerprint);
+    /// assert_eq!(fingerprint[..16], hasher.finish_u128().to_le_bytes());
+    /// ```
+    #[inline]
+    pub fn finish_into(&self, output: &mut [u8]) {
+        dispatch!(B => unsafe { squeeze::<B>(self.flushed_state::<B>(), output, self.profile) })
+    }
+
+    /// Sets the round profile of this hasher. See [`Profile`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use gxhash::{GxHasher, Profile};
+    ///
+    /// let mut hasher = GxHasher::with_seed(1234).with_profile(Profile::Hardened);
+    ///
+    /// hasher.write(b"Hello");
+    ///
+    /// println!("Hash is {:x}!", hasher.finish());
+    /// ```
+    #[inline]
+    pub fn with_profile(mut self, profile: Profile) -> GxHasher {
+        self.profile = profile;
+        self
+    }
+
+    /// Size in bytes of a serialized hasher.
+    pub const SERIALIZED_SIZE: usize = VECTOR_SIZE * 2 + 1;
+
+    /// Serializes the state of this hasher, including its pending bytes, so that hashing can be resumed later
+    /// with [`GxHasher::from_bytes`], possibly in another process or on another supported platform.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use gxhash::GxHasher;
+    ///
+    /// let mut hasher = GxHasher::with_seed(1234);
+    /// hasher.write(b"Hello");
+    ///
+    /// let mut resumed = GxHasher::from_bytes(&hasher.to_bytes()).unwrap();
+    ///
+    /// hasher.write(b"World");
+    /// resumed.write(b"World");
+    ///
+    /// assert_eq!(hasher.finish(), resumed.finish());
+    /// ```
+    #[inline]
+    pub fn to_bytes(&self) -> [u8; GxHasher::SERIALIZED_SIZE] {
+        let mut bytes = [0u8; GxHasher::SERIALIZED_SIZE];
+        bytes[..VECTOR_SIZE].copy_from_slice(&unsafe { core::mem::transmute::<State, [u8; VECTOR_SIZE]>(self.state) });
+        bytes[VECTOR_SIZE..VECTOR_SIZE * 2].copy_from_slice(&self.pending.bytes);
+        bytes[VECTOR_SIZE * 2] = self.pending.len as u8;
+        bytes
+    }
+
+    /// Restores a hasher serialized with [`GxHasher::to_bytes`].
+    ///
+    /// The round profile is not part of the serialized state: the hasher is restored with the default
+    /// profile, use [`GxHasher::with_profile`] to restore another one.
+    ///
+    /// Returns `None` if the bytes are not a valid serialized hasher.
+    #[inline]
+    pub fn from_bytes(bytes: &[u8]) -> Option<GxHasher> {
+        if bytes.len() != GxHasher::SERIALIZED_SIZE {
+            return None;
+        }
+        let pending_len = bytes[VECTOR_SIZE * 2] as usize;
+        if pending_len > VECTOR_SIZE || bytes[VECTOR_SIZE + pending_len..VECTOR_SIZE * 2].iter().any(|&b| b != 0) {
+            return None;
         }
+        let mut hasher = GxHasher::with_state(unsafe { load_unaligned(bytes.as_ptr() as *const State) });
+        hasher.pending.bytes.copy_from_slice(&bytes[VECTOR_SIZE..VECTOR_SIZE * 2]);
+        hasher.pending.len = pending_len;
+        Some(hasher)
     }
 }
 
 macro_rules! write {
-    ($name:ident, $type:ty, $load:expr) => {
+    ($name:ident, $type:ty) => {
         #[inline]
         fn $name(&mut self, value: $type) {
-            self.state = unsafe {
-                aes_encrypt_last($load(value), aes_encrypt(self.state, ld(KEYS.as_ptr())))
-            };
+            self.pending.pack(value.to_le_bytes(), &mut self.state, &KEYS);
         }
     }
 }
@@ -105,41 +278,48 @@ macro_rules! write {
 impl Hasher for GxHasher {
     #[inline]
     fn finish(&self) -> u64 {
-        unsafe {
-            let p = &finalize(self.state) as *const State as *const u64;
-            *p
-        }
+        dispatch!(B => unsafe {
+            let p = &finalize_with::<B>(self.flushed_state::<B>(), &KEYS, self.profile) as *const State as *const u64;
+            u64::from_le(*p)
+        })
     }
 
     #[inline]
     fn write(&mut self, bytes: &[u8]) {
+        self.pending.flush(&mut self.state, &KEYS);
         // Improvement: only compress at this stage and finalize in finish
-        self.state = unsafe { aes_encrypt_last(compress_all(bytes), aes_encrypt(self.state, ld(KEYS.as_ptr()))) };
-    }
-
-    write!(write_u8, u8, load_u8);
-    write!(write_u16, u16, load_u16);
-    write!(write_u32, u32, load_u32);
-    write!(write_u64, u64, load_u64);
-    write!(write_u128, u128, load_u128);
-    write!(write_i8, i8, load_i8);
-    write!(write_i16, i16, load_i16);
-    write!(write_i32, i32, load_i32);
-    write!(write_i64, i64, load_i64);
-    write!(write_i128, i128, load_i128);
+        self.state = dispatch!(B => unsafe { B::aes_encrypt_last(compress_all_with::<B>(bytes, &KEYS, self.profile), B::aes_encrypt(self.state, ld(KEYS.as_ptr()))) });
+    }
+
+    write!(write_u8, u8);
+    write!(write_u16, u16);
+    write!(write_u32, u32);
+    write!(write_u64, u64);
+    write!(write_u128, u128);
+    write!(write_i8, i8);
+    write!(write_i16, i16);
+    write!(write_i32, i32);
+    write!(write_i64, i64);
+    write!(write_i128, i128);
+
+    // Hashed as 64-bit integers, so that hashes are the same on 32-bit and 64-bit platforms
+    #[inline]
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    #[inline]
+    fn write_isize(&mut self, value: isize) {
+        self.write_i64(value as i64);
+    }
 }
 
 /// A builder for building GxHasher with randomized seeds by default, for improved DOS resistance.
 #[derive(Clone, Debug)]
-pub struct GxBuildHasher(State);
-
-#[cfg(not(feature = "deterministic"))]
-#[rustversion::before(1.76)]
-use std::collections::hash_map::RandomState;
-
-#[cfg(not(feature = "deterministic"))]
-#[rustversion::since(1.76)]
-use std::hash::RandomState;
+pub struct GxBuildHasher {
+    pub(crate) state: State,
+    profile: Profile,
+}
 
 impl GxBuildHasher {
     /// Creates a new builder using the provided seed.
@@ -150,21 +330,169 @@ impl GxBuildHasher {
     #[inline]
     pub fn with_seed(seed: i64) -> GxBuildHasher {
         // Use gxhash64 to generate an initial state from a seed
-        GxBuildHasher(unsafe { create_seed(seed) })
+        GxBuildHasher::with_state(unsafe { create_seed(seed) })
+    }
+
+    /// Creates a new builder with a fixed seed, for use cases that require hashes to be repeatable
+    /// from one run to another. The seed is the same as with the former `deterministic` feature.
+    ///
+    /// # Warning ⚠️
+    /// A fixed seed makes your [`Hasher`] vulnerable to DOS attacks.
+    /// It is recommended to use [`GxBuildHasher::default()`] for improved DOS resistance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gxhash::GxBuildHasher;
+    /// use std::hash::BuildHasher;
+    ///
+    /// assert_eq!(GxBuildHasher::deterministic().hash_one("hello"), GxBuildHasher::deterministic().hash_one("hello"));
+    /// ```
+    #[inline]
+    pub fn deterministic() -> GxBuildHasher {
+        GxBuildHasher::with_state(unsafe { load_u128(42) })
+    }
+
+    /// Creates a new builder using the provided 128-bit seed.
+    /// See [`GxHasher::with_seed128`].
+    ///
+    /// # Warning ⚠️
+    /// Hardcoding a seed may make your [`Hasher`] vulnerable to DOS attacks.
+    /// It is recommended to use [`GxBuildHasher::default()`] for improved DOS resistance.
+    #[inline]
+    pub fn with_seed128(seed: u128) -> GxBuildHasher {
+        GxBuildHasher::with_state(dispatch!(B => unsafe { create_seed128::<B>(seed) }))
+    }
+
+    /// Derives a builder for an independent key space identified by the given domain label, from the seed
+    /// of this builder. The label is absorbed once into the seed of the derived builder, so that hashes of
+    /// different domains are uncorrelated without hashing the label again on every call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gxhash::{GxBuildHasher, HashMap};
+    ///
+    /// let root = GxBuildHasher::default();
+    /// let mut sessions: HashMap<&str, u64> = HashMap::with_hasher(root.derive(b"sessions"));
+    /// let mut shards: HashMap<&str, u64> = HashMap::with_hasher(root.derive(b"shards"));
+    /// sessions.insert("user-42", 1);
+    /// shards.insert("user-42", 7);
+    /// ```
+    #[inline]
+    pub fn derive(&self, domain: &[u8]) -> GxBuildHasher {
+        GxBuildHasher { state: dispatch!(B => unsafe { derive_state::<B>(self.state, domain) }), profile: self.profile }
+    }
+
+    /// Sets the round profile of the hashers built by this builder. See [`Profile`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gxhash::{GxBuildHasher, HashMap, Profile};
+    ///
+    /// let mut map: HashMap<&str, u64> = HashMap::with_hasher(GxBuildHasher::default().with_profile(Profile::Fast));
+    /// map.insert("answer", 42);
+    /// ```
+    #[inline]
+    pub fn with_profile(mut self, profile: Profile) -> GxBuildHasher {
+        self.profile = profile;
+        self
+    }
+
+    /// Returns the effective seed of this builder (its full 128-bit internal state), for logging.
+    ///
+    /// Setting the `GXHASH_SEED` environment variable to this value makes [`GxBuildHasher::default()`]
+    /// build this same builder (round profile aside), so that a run can be reproduced.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gxhash::GxBuildHasher;
+    ///
+    /// let build_hasher = GxBuildHasher::default();
+    /// println!("Hashing with GXHASH_SEED={:#x}", build_hasher.seed());
+    /// ```
+    #[inline]
+    pub fn seed(&self) -> u128 {
+        u128::from_le(unsafe { core::mem::transmute::<State, u128>(self.state) })
+    }
+
+    /// Hashes many keys to u64s, as [`BuildHasher::hash_one`] would for each of them.
+    ///
+    /// Keys are hashed by groups of 8, and the final AES rounds of each group are interleaved. For keys such as
+    /// short strings, this is about a quarter faster than calling [`BuildHasher::hash_one`] in a loop.
+    /// See also [`gxhash64_batch`](crate::gxhash64_batch) for keys that are byte slices.
+    ///
+    /// # Panics
+    /// Panics if `keys` and `output` don't have the same length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::hash::BuildHasher;
+    /// use gxhash::GxBuildHasher;
+    ///
+    /// let build_hasher = GxBuildHasher::with_seed(1234);
+    /// let keys = [1u64, 2, 3, 4, 5];
+    /// let mut hashes = [0u64; 5];
+    /// build_hasher.hash_batch(&keys, &mut hashes);
+    /// assert_eq!(build_hasher.hash_one(3u64), hashes[2]);
+    /// ```
+    // BuildHasher::hash_one requires Rust 1.71
+    #[allow(clippy::manual_hash_one)]
+    #[inline]
+    pub fn hash_batch<T: Hash>(&self, keys: &[T], output: &mut [u64]) {
+        assert_eq!(keys.len(), output.len(), "There must be as many outputs as keys");
+        let mut key_groups = keys.chunks_exact(LANES);
+        let mut output_groups = output.chunks_exact_mut(LANES);
+        for (keys, output) in (&mut key_groups).zip(&mut output_groups) {
+            let mut states = [self.state; LANES];
+            for (state, key) in states.iter_mut().zip(keys) {
+                let mut hasher = self.build_hasher();
+                key.hash(&mut hasher);
+                *state = dispatch!(B => unsafe { hasher.flushed_state::<B>() });
+            }
+            dispatch!(B => unsafe { store_u64(finalize_lanes::<B>(states, self.profile), output) });
+        }
+        for (key, hash) in key_groups.remainder().iter().zip(output_groups.into_remainder()) {
+            let mut hasher = self.build_hasher();
+            key.hash(&mut hasher);
+            *hash = hasher.finish();
+        }
+    }
+
+    #[inline]
+    fn with_state(state: State) -> GxBuildHasher {
+        GxBuildHasher { state, profile: Profile::Default }
     }
 }
 
 impl Default for GxBuildHasher {
+    /// Creates a new builder with a random seed, from the source set with [`set_entropy_source`](crate::set_entropy_source)
+    /// if any, or else from OS randomness with the `std` feature, or from `RDRAND` on x86 without it.
+    ///
+    /// With the `std` feature, OS randomness is read once per process and each builder gets a fresh seed derived
+    /// from a per-thread key, so creating a builder only costs a few AES rounds. On unix, child processes re-seed
+    /// after a `fork`.
+    ///
+    /// With the `std` feature, setting the `GXHASH_SEED` environment variable to a decimal or `0x`-prefixed
+    /// hexadecimal 128-bit integer overrides the random seed for the whole process, for instance to reproduce
+    /// a bug with a seed logged with [`GxBuildHasher::seed`]. This of course disables DOS mitigation.
+    ///
+    /// # Panics
+    /// Panics if `GXHASH_SEED` is set but is not a valid seed.
+    /// Without the `std` feature, panics if no source of randomness is available.
+    /// Use [`GxBuildHasher::with_seed`] with a seed obtained by other means in that case.
     #[inline]
     fn default() -> GxBuildHasher {
-        #[cfg(feature = "deterministic")]
-        let random_state: u128 = 42;
-        #[cfg(not(feature = "deterministic"))]
-        let random_state = RandomState::new();
-        unsafe {
-            let state: State = std::mem::transmute(random_state);
-            GxBuildHasher(state)
+        #[cfg(feature = "std")]
+        if let Some(seed) = crate::entropy::env_seed() {
+            return GxBuildHasher::with_state(unsafe { load_u128(seed) });
         }
+        let random_state = crate::entropy::random_u128()
+            .expect("No source of randomness available, see gxhash::set_entropy_source");
+        GxBuildHasher::with_state(unsafe { load_u128(random_state) })
     }
 }
 
@@ -172,16 +500,18 @@ impl BuildHasher for GxBuildHasher {
     type Hasher = GxHasher;
     #[inline]
     fn build_hasher(&self) -> GxHasher {
-        GxHasher::with_state(self.0)
+        GxHasher::with_state(self.state).with_profile(self.profile)
     }
 }
 
 /// A `HashMap` using a (DOS-resistant) [`GxBuildHasher`].
+#[cfg(feature = "std")]
 pub type HashMap<K, V> = std::collections::HashMap<K, V, GxBuildHasher>;
 
 /// A convenience trait that can be used together with the type aliases defined
 /// to get access to the `new()` and `with_capacity()` methods for the
 /// [`HashMap`] type alias.
+#[cfg(feature = "std")]
 pub trait HashMapExt {
     /// Constructs a new HashMap.
     fn new() -> Self;
@@ -189,6 +519,7 @@ pub trait HashMapExt {
     fn with_capacity(capacity: usize) -> Self;
 }
 
+#[cfg(feature = "std")]
 impl<K, V, S> HashMapExt for std::collections::HashMap<K, V, S>
 where
     S: BuildHasher + Default,
@@ -203,11 +534,13 @@ where
 }
 
 /// A `HashSet` using a (DOS-resistant) [`GxBuildHasher`].
+#[cfg(feature = "std")]
 pub type HashSet<T> = std::collections::HashSet<T, GxBuildHasher>;
 
 /// A convenience trait that can be used together with the type aliases defined
 /// to get access to the `new()` and `with_capacity()` methods for the
 /// [`HashSet`] type alias.
+#[cfg(feature = "std")]
 pub trait HashSetExt {
     /// Constructs a new HashMap.
     fn new() -> Self;
@@ -215,6 +548,7 @@ pub trait HashSetExt {
     fn with_capacity(capacity: usize) -> Self;
 }
 
+#[cfg(feature = "std")]
 impl<K, S> HashSetExt for std::collections::HashSet<K, S>
     where S: BuildHasher + Default,
 {
@@ -235,6 +569,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn contructors_work() {
         let mut map: std::collections::HashMap<&str, i32, GxBuildHasher> = HashMap::new();
         assert_eq!(true, map.insert("foo", 1).is_none());
@@ -267,6 +602,7 @@ mod tests {
 
     // By no mean a quality test, but rather a sanity check
     #[test]
+    #[allow(clippy::manual_hash_one)]
     fn hasher_resists_permutations() {
         let build_hasher = GxBuildHasher::default();
         let mut hasher1 = build_hasher.build_hasher();
@@ -291,11 +627,7 @@ mod tests {
         hasher_2.write_i32(42);
         let hash_2 = hasher_2.finish();
 
-        if cfg!(feature = "deterministic") {
-            assert_eq!(hash_1, hash_2);
-        } else {
-            assert_ne!(hash_1, hash_2);
-        }
+        assert_ne!(hash_1, hash_2);
     }
 
     // This is important for DOS resistance
@@ -313,10 +645,201 @@ mod tests {
         hasher_2.write_i32(42);
         let hash_2 = hasher_2.finish();
 
-        if cfg!(feature = "deterministic") {
-            assert_eq!(hash_1, hash_2);
-        } else { 
-            assert_ne!(hash_1, hash_2);
+        assert_ne!(hash_1, hash_2);
+    }
+
+    #[test]
+    fn deterministic_builder_is_stable() {
+        assert_eq!(GxBuildHasher::deterministic().hash_one("hello"), GxBuildHasher::deterministic().hash_one("hello"));
+        assert_eq!(42, GxBuildHasher::deterministic().seed());
+        assert_eq!(2498099297539264013, GxBuildHasher::deterministic().hash_one(42u64));
+    }
+
+    #[test]
+    fn seed_is_the_internal_state() {
+        let build_hasher = GxBuildHasher::default();
+        let seed = build_hasher.seed();
+        let reproduced = GxBuildHasher::with_state(unsafe { load_u128(seed) });
+        assert_eq!(build_hasher.hash_one("hello"), reproduced.hash_one("hello"));
+        assert_eq!(u64::MAX as u128 * 0x1_0000_0000_0000_0001, GxBuildHasher::with_seed(-1).seed());
+    }
+
+    #[test]
+    fn seed128_hashers_match() {
+        let mut hasher = GxHasher::with_seed128(u128::MAX);
+        hasher.write_u32(42);
+        assert_eq!(hasher.finish(), GxBuildHasher::with_seed128(u128::MAX).hash_one(42u32));
+
+        let mut hasher = GxHasher::with_seed128(1 << 64);
+        hasher.write_u32(42);
+        assert_ne!(hasher.finish(), GxBuildHasher::with_seed128(1).hash_one(42u32));
+    }
+
+    #[test]
+    fn finish_into_is_prefix_consistent() {
+        let mut hasher = GxHasher::with_seed(42).with_profile(Profile::Hardened);
+        hasher.write(b"Hello World");
+
+        let mut output = [0u8; 64];
+        hasher.finish_into(&mut output);
+        assert_eq!(output[..16], hasher.finish_u128().to_le_bytes());
+        assert_eq!(output[..8], hasher.finish().to_le_bytes());
+
+        let mut short = [0u8; 20];
+        hasher.finish_into(&mut short);
+        assert_eq!(short, output[..20]);
+    }
+
+    #[test]
+    fn derived_builders_are_independent() {
+        let root = GxBuildHasher::with_seed(42);
+        let sessions = root.derive(b"sessions");
+        assert_ne!(root.hash_one("user-42"), sessions.hash_one("user-42"));
+        assert_ne!(sessions.hash_one("user-42"), root.derive(b"users").hash_one("user-42"));
+        assert_eq!(sessions.hash_one("user-42"), root.derive(b"sessions").hash_one("user-42"));
+        assert_ne!(sessions.hash_one("user-42"), GxBuildHasher::with_seed(43).derive(b"sessions").hash_one("user-42"));
+        assert_ne!(sessions.hash_one("user-42"), sessions.derive(b"sessions").hash_one("user-42"));
+    }
+
+    #[test]
+    fn hasher_can_be_resumed_from_bytes() {
+        let mut hasher = GxHasher::with_seed(42);
+        hasher.write(b"Hello");
+        hasher.write_u64(42);
+
+        let bytes = hasher.to_bytes();
+        assert_eq!(bytes, GxHasher::from_bytes(&bytes).unwrap().to_bytes());
+
+        let mut resumed = GxHasher::from_bytes(&bytes).unwrap();
+        hasher.write_u32(7);
+        resumed.write_u32(7);
+        hasher.write(b"World");
+        resumed.write(b"World");
+        assert_eq!(hasher.finish_u128(), resumed.finish_u128());
+
+        let mut invalid = bytes;
+        invalid[GxHasher::SERIALIZED_SIZE - 1] = VECTOR_SIZE as u8 + 1;
+        assert!(GxHasher::from_bytes(&invalid).is_none());
+        let mut invalid = bytes;
+        invalid[VECTOR_SIZE * 2 - 1] = 1;
+        assert!(GxHasher::from_bytes(&invalid).is_none());
+        assert!(GxHasher::from_bytes(&bytes[1..]).is_none());
+    }
+
+    #[test]
+    fn small_writes_are_packed() {
+        let build_hasher = GxBuildHasher::with_seed(42);
+        let hash = |f: &dyn Fn(&mut GxHasher)| {
+            let mut hasher = build_hasher.build_hasher();
+            f(&mut hasher);
+            hasher.finish()
+        };
+
+        // Packing doesn't depend on how the pending bytes were written
+        assert_eq!(hash(&|h| { h.write_u32(0x04030201); h.write_u8(5); }), hash(&|h| { h.write_u8(1); h.write_u32(0x05040302); }));
+        // Trailing zeroes are part of the hash
+        assert_ne!(hash(&|h| h.write_u8(1)), hash(&|h| h.write_u16(1)));
+        assert_ne!(hash(&|h| h.write_u8(0)), hash(&|_| {}));
+        // Packed integers and slices don't collide
+        assert_ne!(hash(&|h| h.write_u32(42)), hash(&|h| h.write(&42u32.to_le_bytes())));
+        // Writes that don't fit in the pending bytes still affect the hash
+        let mut hashes = std::collections::HashSet::new();
+        for i in 0..64u8 {
+            assert!(hashes.insert(hash(&|h| { for j in 0..64u8 { h.write_u8(if i == j { 1 } else { 0 }); } })));
+        }
+        assert_eq!(hash(&|h| h.write_usize(42)), hash(&|h| h.write_u64(42)));
+    }
+
+    fn hash_with_profile(profile: Profile, bytes: &[u8], seed: i64) -> u64 {
+        let mut hasher = GxHasher::with_seed(seed).with_profile(profile);
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn default_profile_is_stable() {
+        assert_eq!(17118817743232439212, hash_with_profile(Profile::Default, &[0u8; 0], 0));
+        assert_eq!(7327909443358324775, hash_with_profile(Profile::Default, &[0u8; 1], 0));
+        assert_eq!(5883755436067122028, hash_with_profile(Profile::Default, &[0u8; 17], 0));
+        assert_eq!(11788951698970275222, hash_with_profile(Profile::Default, &[0u8; 1000], 0));
+        assert_eq!(7900268075770288720, hash_with_profile(Profile::Default, &[42u8; 4242], 42));
+        assert_eq!(17456908627534570045, hash_with_profile(Profile::Default, b"Hello World", i64::MAX));
+        assert_eq!(728277310244424816, GxBuildHasher::with_seed(0).hash_one(42u64));
+        assert_eq!(7638860153610022286, GxBuildHasher::with_seed(0).hash_one((42u32, 7u16, 3u8, 1234u64, true)));
+        assert_eq!(14612407285696764217, GxBuildHasher::with_seed(0).hash_one((1u64, 2u64, 3u64)));
+    }
+
+    #[test]
+    fn fast_profile_is_stable() {
+        assert_eq!(16530053468759865104, hash_with_profile(Profile::Fast, &[0u8; 0], 0));
+        assert_eq!(16125810019349056369, hash_with_profile(Profile::Fast, &[0u8; 1], 0));
+        assert_eq!(5192384469499899968, hash_with_profile(Profile::Fast, &[0u8; 17], 0));
+        assert_eq!(2125950606179126903, hash_with_profile(Profile::Fast, &[0u8; 1000], 0));
+        assert_eq!(2444860023452912134, hash_with_profile(Profile::Fast, &[42u8; 4242], 42));
+        assert_eq!(103462136043291861, hash_with_profile(Profile::Fast, b"Hello World", i64::MAX));
+        assert_eq!(15314072464940854856, GxBuildHasher::with_seed(0).with_profile(Profile::Fast).hash_one(42u64));
+    }
+
+    #[test]
+    fn hardened_profile_is_stable() {
+        assert_eq!(1789872732105287606, hash_with_profile(Profile::Hardened, &[0u8; 0], 0));
+        assert_eq!(1702848349193966246, hash_with_profile(Profile::Hardened, &[0u8; 1], 0));
+        assert_eq!(16700976505913243583, hash_with_profile(Profile::Hardened, &[0u8; 17], 0));
+        assert_eq!(7931189387731739756, hash_with_profile(Profile::Hardened, &[0u8; 1000], 0));
+        assert_eq!(13572559514461050401, hash_with_profile(Profile::Hardened, &[42u8; 4242], 42));
+        assert_eq!(14593949720842896683, hash_with_profile(Profile::Hardened, b"Hello World", i64::MAX));
+        assert_eq!(9638618697298577406, GxBuildHasher::with_seed(0).with_profile(Profile::Hardened).hash_one(42u64));
+    }
+
+    #[test]
+    fn profiles_produce_different_hashes() {
+        for len in [0, 1, 17, 64, 1000] {
+            let bytes = vec![42u8; len];
+            let fast = hash_with_profile(Profile::Fast, &bytes, 0);
+            let default = hash_with_profile(Profile::Default, &bytes, 0);
+            let hardened = hash_with_profile(Profile::Hardened, &bytes, 0);
+            assert_ne!(fast, default);
+            assert_ne!(default, hardened);
+            assert_ne!(fast, hardened);
+        }
+    }
+
+    #[test]
+    fn profiles_avalanche() {
+        // Probability for each output bit to flip when any single input bit flips, which must be about one half
+        fn flip_probabilities(bits: u32, hash: impl Fn(u64) -> u64) -> Vec<f64> {
+            use rand::Rng;
+
+            let mut flips = [0u64; 64];
+            let mut rng = rand::thread_rng();
+            for _ in 0..500 {
+                let value = rng.gen::<u64>() >> (64 - bits);
+                let hash1 = hash(value);
+                for i in 0..bits {
+                    let diff = hash1 ^ hash(value ^ (1 << i));
+                    for (k, count) in flips.iter_mut().enumerate() {
+                        *count += (diff >> k) & 1;
+                    }
+                }
+            }
+            flips.iter().map(|&count| count as f64 / (500 * bits) as f64).collect()
+        }
+
+        for profile in [Profile::Fast, Profile::Default, Profile::Hardened] {
+            let build_hasher = GxBuildHasher::with_seed(42).with_profile(profile);
+            let write = |value: u64| {
+                let mut hasher = build_hasher.build_hasher();
+                hasher.write(&value.to_le_bytes());
+                hasher.finish()
+            };
+            let u32s = flip_probabilities(32, |value| build_hasher.hash_one(value as u32));
+            let u64s = flip_probabilities(64, |value| build_hasher.hash_one(value));
+            let writes = flip_probabilities(64, write);
+            for (name, probabilities) in [("u32", u32s), ("u64", u64s), ("write", writes)] {
+                for (k, probability) in probabilities.into_iter().enumerate() {
+                    assert!((0.47..0.53).contains(&probability), "{profile:?}, {name}: output bit {k} flips with probability {probability}");
+                }
+            }
         }
     }
 
@@ -334,4 +857,24 @@ mod tests {
         hasher.write_i32(42);
         assert_eq!(hash, hasher.finish());
     }
+    #[test]
+    fn hash_batch_is_hash_one() {
+        for profile in [Profile::Fast, Profile::Default, Profile::Hardened] {
+            let build_hasher = GxBuildHasher::with_seed(42).with_profile(profile);
+            for count in 0..10 {
+                let keys: Vec<(u32, String)> = (0..count).map(|i| (i, "x".repeat(i as usize * 7))).collect();
+                let mut hashes = vec![0u64; keys.len()];
+                build_hasher.hash_batch(&keys, &mut hashes);
+                for (key, hash) in keys.iter().zip(&hashes) {
+                    assert_eq!(build_hasher.hash_one(key), *hash, "{profile:?}, key {key:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "There must be as many outputs as keys")]
+    fn hash_batch_output_of_other_length_panics() {
+        GxBuildHasher::with_seed(42).hash_batch(&[1u64, 2, 3], &mut [0u64; 2]);
+    }
 }
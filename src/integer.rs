@@ -0,0 +1,233 @@
+use core::hash::{BuildHasher, Hasher};
+
+use crate::gxhash::platform::*;
+use crate::gxhash::*;
+
+// Integers up to 64 bits are broadcast to all lanes of the state, so that every column of the state is
+// already active after the first AES round, and two rounds are enough for full avalanche. 128-bit integers
+// have no redundancy across lanes and need one more round.
+
+#[inline(always)]
+pub(crate) unsafe fn absorb<B: Aes>(state: State, value: State) -> State {
+    B::aes_encrypt(add_bytes(state, value), ld(KEYS.as_ptr()))
+}
+
+#[inline(always)]
+pub(crate) unsafe fn absorb_wide<B: Aes>(state: State, value: State) -> State {
+    B::aes_encrypt(absorb::<B>(state, value), ld(KEYS.as_ptr().offset(8)))
+}
+
+#[inline(always)]
+pub(crate) unsafe fn finish_u64<B: Aes>(state: State) -> u64 {
+    let hash = B::aes_encrypt(state, ld(KEYS.as_ptr().offset(4)));
+    let p = &hash as *const State as *const u64;
+    u64::from_le(*p)
+}
+
+/// Hashes an u32 to an u64, with fewer AES rounds than hashing its bytes with [`gxhash64`].
+/// The hash is the same as [`GxIntBuildHasher::hash_one`](BuildHasher::hash_one) with the same seed.
+///
+/// # Example
+///
+/// ```
+/// println!("Hash is {:x}!", gxhash::hash_u32(42, 1234));
+/// ```
+#[inline(always)]
+pub fn hash_u32(value: u32, seed: i64) -> u64 {
+    dispatch!(B => unsafe { finish_u64::<B>(absorb::<B>(create_seed(seed), load_u32(value))) })
+}
+
+/// Hashes an u64 to an u64, with fewer AES rounds than hashing its bytes with [`gxhash64`].
+/// The hash is the same as [`GxIntBuildHasher::hash_one`](BuildHasher::hash_one) with the same seed.
+///
+/// # Example
+///
+/// ```
+/// println!("Hash is {:x}!", gxhash::hash_u64(42, 1234));
+/// ```
+#[inline(always)]
+pub fn hash_u64(value: u64, seed: i64) -> u64 {
+    dispatch!(B => unsafe { finish_u64::<B>(absorb::<B>(create_seed(seed), load_u64(value))) })
+}
+
+/// Hashes an u128 to an u64, with fewer AES rounds than hashing its bytes with [`gxhash64`].
+/// The hash is the same as [`GxIntBuildHasher::hash_one`](BuildHasher::hash_one) with the same seed.
+///
+/// # Example
+///
+/// ```
+/// println!("Hash is {:x}!", gxhash::hash_u128(42, 1234));
+/// ```
+#[inline(always)]
+pub fn hash_u128(value: u128, seed: i64) -> u64 {
+    dispatch!(B => unsafe { finish_u64::<B>(absorb_wide::<B>(create_seed(seed), load_u128(value))) })
+}
+
+/// A `Hasher` specialized for integer keys, built from a [`GxIntBuildHasher`].
+///
+/// Integers are hashed with two AES rounds (three for 128-bit integers). Other keys are supported, their bytes
+/// being compressed as with [`GxHasher`](crate::GxHasher) first, but a [`GxBuildHasher`](crate::GxBuildHasher)
+/// is a better fit for keys that are not integers.
+///
+/// Unlike [`GxHasher`](crate::GxHasher), small writes are not packed together: keys are expected to be single
+/// integers, for which packing would only add a copy through a buffer before the same rounds.
+#[derive(Clone, Debug)]
+pub struct GxIntHasher {
+    state: State,
+}
+
+macro_rules! write {
+    ($name:ident, $type:ty, $load:expr) => {
+        #[inline]
+        fn $name(&mut self, value: $type) {
+            self.state = dispatch!(B => unsafe { absorb::<B>(self.state, $load(value)) });
+        }
+    }
+}
+
+macro_rules! write_wide {
+    ($name:ident, $type:ty, $load:expr) => {
+        #[inline]
+        fn $name(&mut self, value: $type) {
+            self.state = dispatch!(B => unsafe { absorb_wide::<B>(self.state, $load(value)) });
+        }
+    }
+}
+
+impl Hasher for GxIntHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        dispatch!(B => unsafe { finish_u64::<B>(self.state) })
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        // Small inputs are only loaded by the compression, so they are absorbed like 128-bit integers
+        self.state = dispatch!(B => unsafe { absorb_wide::<B>(self.state, compress_all_with::<B>(bytes, &KEYS, Profile::Fast)) });
+    }
+
+    #[inline]
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    #[inline]
+    fn write_isize(&mut self, value: isize) {
+        self.write_i64(value as i64);
+    }
+
+    write!(write_u8, u8, load_u8);
+    write!(write_u16, u16, load_u16);
+    write!(write_u32, u32, load_u32);
+    write!(write_u64, u64, load_u64);
+    write_wide!(write_u128, u128, load_u128);
+    write!(write_i8, i8, load_i8);
+    write!(write_i16, i16, load_i16);
+    write!(write_i32, i32, load_i32);
+    write!(write_i64, i64, load_i64);
+    write_wide!(write_i128, i128, load_i128);
+}
+
+/// A builder for building [`GxIntHasher`], for maps and sets with integer keys.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use std::hash::BuildHasher;
+/// use gxhash::GxIntBuildHasher;
+///
+/// let mut map: HashMap<u64, &str, GxIntBuildHasher> = HashMap::default();
+/// map.insert(42, "answer");
+///
+/// let build_hasher = GxIntBuildHasher::with_seed(1234);
+/// assert_eq!(gxhash::hash_u64(42, 1234), build_hasher.hash_one(42u64));
+/// ```
+#[derive(Clone, Debug)]
+pub struct GxIntBuildHasher {
+    state: State,
+}
+
+impl GxIntBuildHasher {
+    /// Creates a new builder using the provided seed.
+    ///
+    /// # Warning ⚠️
+    /// Hardcoding a seed may make your [`Hasher`] vulnerable to DOS attacks.
+    /// It is recommended to use [`GxIntBuildHasher::default()`] for improved DOS resistance.
+    #[inline]
+    pub fn with_seed(seed: i64) -> GxIntBuildHasher {
+        GxIntBuildHasher { state: unsafe { create_seed(seed) } }
+    }
+}
+
+impl Default for GxIntBuildHasher {
+    /// Creates a new builder with a random seed, obtained the same way as for
+    /// [`GxBuildHasher::default()`](crate::GxBuildHasher::default).
+    ///
+    /// # Panics
+    /// Panics in the same cases as [`GxBuildHasher::default()`](crate::GxBuildHasher::default).
+    #[inline]
+    fn default() -> GxIntBuildHasher {
+        GxIntBuildHasher { state: crate::GxBuildHasher::default().state }
+    }
+}
+
+impl BuildHasher for GxIntBuildHasher {
+    type Hasher = GxIntHasher;
+    #[inline]
+    fn build_hasher(&self) -> GxIntHasher {
+        GxIntHasher { state: self.state }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn hash_one_is_hash_function() {
+        let mut rng = rand::thread_rng();
+        let build_hasher = GxIntBuildHasher::with_seed(42);
+        for _ in 0..1000 {
+            let value: u128 = rng.gen();
+            assert_eq!(hash_u32(value as u32, 42), build_hasher.hash_one(value as u32));
+            assert_eq!(hash_u64(value as u64, 42), build_hasher.hash_one(value as u64));
+            assert_eq!(hash_u64(value as u64, 42), build_hasher.hash_one(value as usize));
+            assert_eq!(hash_u128(value, 42), build_hasher.hash_one(value));
+        }
+    }
+
+    #[test]
+    fn seed_changes_hash() {
+        for value in [0u64, 1, 42, u64::MAX] {
+            assert_ne!(hash_u64(value, 0), hash_u64(value, 1));
+        }
+        assert_ne!(hash_u32(42, 0), hash_u32(42, 1));
+        assert_ne!(hash_u128(42, 0), hash_u128(42, 1));
+    }
+
+    #[test]
+    fn sequential_integers_do_not_collide() {
+        let hashes: std::collections::HashSet<u64> = (0..100_000u64).map(|i| hash_u64(i, 0)).collect();
+        assert_eq!(100_000, hashes.len());
+        let hashes: std::collections::HashSet<u64> = (0..100_000u32).map(|i| hash_u32(i, 0)).collect();
+        assert_eq!(100_000, hashes.len());
+    }
+
+    #[test]
+    fn write_order_matters() {
+        let build_hasher = GxIntBuildHasher::with_seed(0);
+        assert_ne!(build_hasher.hash_one((1u64, 2u64)), build_hasher.hash_one((2u64, 1u64)));
+        assert_ne!(build_hasher.hash_one("hello"), build_hasher.hash_one("world"));
+    }
+
+    #[test]
+    fn is_stable() {
+        assert_eq!(14541561720874560196, hash_u32(42, 0));
+        assert_eq!(15415901355604241328, hash_u64(42, 0));
+        assert_eq!(6119509639642316016, hash_u128(42, 0));
+        assert_eq!(6377312721248669774, GxIntBuildHasher::with_seed(0).hash_one("hello"));
+    }
+}
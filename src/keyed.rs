@@ -0,0 +1,308 @@
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+
+use crate::gxhash::platform::*;
+use crate::gxhash::*;
+use crate::hasher::Pending;
+
+/// A secret from which custom round keys are derived, replacing the public constants used by GxHash.
+///
+/// With public round keys, the seed is the only secret an attacker has to work around when crafting
+/// collisions. With a [`GxSecret`], the rounds of the compression and of the finalization themselves
+/// are unknown to the attacker.
+///
+/// # Example
+///
+/// ```
+/// use gxhash::GxSecret;
+///
+/// let secret = GxSecret::from_key128(0x0123456789abcdef0123456789abcdef);
+/// println!("Hash is {:x}!", gxhash::gxhash64_keyed(b"Hello World", 1234, &secret));
+/// ```
+#[derive(Clone)]
+pub struct GxSecret {
+    keys: [u32; 12],
+}
+
+impl GxSecret {
+    /// Derives a secret from a 128-bit key.
+    ///
+    /// This is equivalent to [`GxSecret::from_key256`] with the upper 128 bits of the key set to zero.
+    #[inline]
+    pub fn from_key128(key: u128) -> GxSecret {
+        let mut bytes = [0u8; 32];
+        bytes[..16].copy_from_slice(&key.to_le_bytes());
+        GxSecret::from_key256(bytes)
+    }
+
+    /// Derives a secret from a 256-bit key.
+    pub fn from_key256(key: [u8; 32]) -> GxSecret {
+        let mut keys = [0u32; 12];
+        dispatch!(B => unsafe {
+            let mut state = load_unaligned(key.as_ptr() as *const State);
+            let key_hi = load_unaligned(key.as_ptr().add(VECTOR_SIZE) as *const State);
+            // Each round key is derived from the previous one, mixed with both halves of the key
+            for (i, round_key) in keys.chunks_exact_mut(4).enumerate() {
+                state = B::aes_encrypt(state, ld(KEYS.as_ptr().add(i * 4)));
+                state = B::aes_encrypt(state, key_hi);
+                state = B::aes_encrypt_last(state, ld(KEYS.as_ptr().add(i * 4)));
+                round_key.copy_from_slice(&core::mem::transmute::<State, [u32; 4]>(state).map(u32::from_le));
+            }
+        });
+        GxSecret { keys }
+    }
+}
+
+impl fmt::Debug for GxSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never leak the round keys
+        f.debug_struct("GxSecret").finish_non_exhaustive()
+    }
+}
+
+/// Hashes an arbitrary stream of bytes to an u32, using the round keys of the given secret.
+///
+/// # Example
+///
+/// ```
+/// let secret = gxhash::GxSecret::from_key128(42);
+/// println!("Hash is {:x}!", gxhash::gxhash32_keyed(b"Hello World", 1234, &secret));
+/// ```
+#[inline(always)]
+pub fn gxhash32_keyed(input: &[u8], seed: i64, secret: &GxSecret) -> u32 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_with::<B>(input, create_seed(seed), &secret.keys, Profile::Default) as *const State as *const u32;
+        u32::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u64, using the round keys of the given secret.
+///
+/// # Example
+///
+/// ```
+/// let secret = gxhash::GxSecret::from_key128(42);
+/// println!("Hash is {:x}!", gxhash::gxhash64_keyed(b"Hello World", 1234, &secret));
+/// ```
+#[inline(always)]
+pub fn gxhash64_keyed(input: &[u8], seed: i64, secret: &GxSecret) -> u64 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_with::<B>(input, create_seed(seed), &secret.keys, Profile::Default) as *const State as *const u64;
+        u64::from_le(*p)
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u128, using the round keys of the given secret.
+///
+/// # Example
+///
+/// ```
+/// let secret = gxhash::GxSecret::from_key128(42);
+/// println!("Hash is {:x}!", gxhash::gxhash128_keyed(b"Hello World", 1234, &secret));
+/// ```
+#[inline(always)]
+pub fn gxhash128_keyed(input: &[u8], seed: i64, secret: &GxSecret) -> u128 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_with::<B>(input, create_seed(seed), &secret.keys, Profile::Default) as *const State as *const u128;
+        u128::from_le(*p)
+    })
+}
+
+/// A `Hasher` for hashing an arbitrary stream of bytes, using the round keys of a [`GxSecret`].
+/// Built from a [`GxKeyedBuildHasher`].
+#[derive(Clone, Debug)]
+pub struct GxKeyedHasher {
+    state: State,
+    pending: Pending,
+    secret: GxSecret,
+}
+
+impl GxKeyedHasher {
+    #[inline]
+    fn with_state(state: State, secret: GxSecret) -> GxKeyedHasher {
+        GxKeyedHasher { state, pending: Pending::default(), secret }
+    }
+
+    /// The state with pending bytes mixed in with the round keys of the secret, if any.
+    #[inline(always)]
+    unsafe fn flushed_state<B: Aes>(&self) -> State {
+        self.pending.mix::<B>(self.state, &self.secret.keys)
+    }
+
+    /// Finish this hasher and return the hashed value as a 128-bit
+    /// unsigned integer.
+    #[inline]
+    pub fn finish_u128(&self) -> u128 {
+        dispatch!(B => unsafe {
+            let p = &finalize_with::<B>(self.flushed_state::<B>(), &self.secret.keys, Profile::Default) as *const State as *const u128;
+            u128::from_le(*p)
+        })
+    }
+}
+
+macro_rules! write {
+    ($name:ident, $type:ty) => {
+        #[inline]
+        fn $name(&mut self, value: $type) {
+            self.pending.pack(value.to_le_bytes(), &mut self.state, &self.secret.keys);
+        }
+    }
+}
+
+impl Hasher for GxKeyedHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        dispatch!(B => unsafe {
+            let p = &finalize_with::<B>(self.flushed_state::<B>(), &self.secret.keys, Profile::Default) as *const State as *const u64;
+            u64::from_le(*p)
+        })
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.pending.flush(&mut self.state, &self.secret.keys);
+        self.state = dispatch!(B => unsafe {
+            B::aes_encrypt_last(compress_all_with::<B>(bytes, &self.secret.keys, Profile::Default), B::aes_encrypt(self.state, ld(self.secret.keys.as_ptr())))
+        });
+    }
+
+    write!(write_u8, u8);
+    write!(write_u16, u16);
+    write!(write_u32, u32);
+    write!(write_u64, u64);
+    write!(write_u128, u128);
+    write!(write_i8, i8);
+    write!(write_i16, i16);
+    write!(write_i32, i32);
+    write!(write_i64, i64);
+    write!(write_i128, i128);
+
+    // Hashed as 64-bit integers, so that hashes are the same on 32-bit and 64-bit platforms
+    #[inline]
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    #[inline]
+    fn write_isize(&mut self, value: isize) {
+        self.write_i64(value as i64);
+    }
+}
+
+/// A builder for building [`GxKeyedHasher`], for use in multi-tenant services where the round keys
+/// should not be the public constants shared by every user.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use gxhash::{GxKeyedBuildHasher, GxSecret};
+///
+/// let secret = GxSecret::from_key128(0x0123456789abcdef0123456789abcdef);
+/// let mut map = HashMap::with_hasher(GxKeyedBuildHasher::new(secret));
+/// map.insert("answer", 42);
+/// ```
+#[derive(Clone, Debug)]
+pub struct GxKeyedBuildHasher {
+    state: State,
+    secret: GxSecret,
+}
+
+impl GxKeyedBuildHasher {
+    /// Creates a new builder using the provided secret and a randomized seed.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn new(secret: GxSecret) -> GxKeyedBuildHasher {
+        GxKeyedBuildHasher { state: crate::GxBuildHasher::default().state, secret }
+    }
+
+    /// Creates a new builder using the provided secret and seed.
+    #[inline]
+    pub fn with_seed(secret: GxSecret, seed: i64) -> GxKeyedBuildHasher {
+        GxKeyedBuildHasher { state: unsafe { create_seed(seed) }, secret }
+    }
+}
+
+impl BuildHasher for GxKeyedBuildHasher {
+    type Hasher = GxKeyedHasher;
+    #[inline]
+    fn build_hasher(&self) -> GxKeyedHasher {
+        GxKeyedHasher::with_state(self.state, self.secret.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn secret_changes_hash() {
+        let secret = GxSecret::from_key128(0);
+        assert_ne!(gxhash64(b"Hello World", 0), gxhash64_keyed(b"Hello World", 0, &secret));
+        for bit in 0..128 {
+            let other = GxSecret::from_key128(1 << bit);
+            assert_ne!(gxhash128_keyed(b"Hello World", 0, &secret), gxhash128_keyed(b"Hello World", 0, &other), "Key bit {bit} not used");
+        }
+        let mut key = [0u8; 32];
+        key[31] = 1;
+        assert_ne!(gxhash128_keyed(b"Hello World", 0, &secret), gxhash128_keyed(b"Hello World", 0, &GxSecret::from_key256(key)));
+    }
+
+    #[test]
+    fn secret_is_used_by_all_rounds() {
+        // Large enough inputs to go through compress_8
+        let secret1 = GxSecret::from_key128(1);
+        let secret2 = GxSecret::from_key128(2);
+        for len in [0, 1, 16, 17, 64, 65, 200, 1000] {
+            let bytes = vec![42u8; len];
+            assert_ne!(gxhash64_keyed(&bytes, 0, &secret1), gxhash64_keyed(&bytes, 0, &secret2));
+        }
+    }
+
+    #[test]
+    fn keyed_build_hasher_is_consistent() {
+        let build_hasher = GxKeyedBuildHasher::with_seed(GxSecret::from_key128(42), 42);
+        assert_eq!(build_hasher.hash_one("hello"), build_hasher.hash_one("hello"));
+        assert_ne!(build_hasher.hash_one((1, 2)), build_hasher.hash_one((2, 1)));
+
+        let mut hasher = build_hasher.build_hasher();
+        hasher.write(b"hello");
+        assert_eq!(hasher.finish(), hasher.finish_u128() as u64);
+    }
+
+    #[test]
+    fn keyed_hasher_packs_small_writes() {
+        let build_hasher = GxKeyedBuildHasher::with_seed(GxSecret::from_key128(42), 42);
+        let hash = |f: &dyn Fn(&mut GxKeyedHasher)| {
+            let mut hasher = build_hasher.build_hasher();
+            f(&mut hasher);
+            hasher.finish_u128()
+        };
+
+        // Packing doesn't depend on how the pending bytes were written
+        assert_eq!(hash(&|h| h.write_u64(0x0807060504030201)), hash(&|h| { h.write_u32(0x04030201); h.write_u32(0x08070605); }));
+        // Writes that don't fit in the pending bytes still affect the hash
+        assert_ne!(hash(&|h| { h.write_u128(1); h.write_u8(2); }), hash(&|h| { h.write_u128(1); h.write_u8(3); }));
+        // Trailing zeroes are part of the hash
+        assert_ne!(hash(&|h| h.write_u32(0)), hash(&|h| h.write_u64(0)));
+        let other = GxKeyedBuildHasher::with_seed(GxSecret::from_key128(43), 42);
+        assert_ne!(build_hasher.hash_one(42u64), other.hash_one(42u64));
+        assert_eq!(build_hasher.hash_one(42usize), build_hasher.hash_one(42u64));
+    }
+
+    #[test]
+    fn keyed_is_stable() {
+        let secret = GxSecret::from_key128(42);
+        assert_eq!(993883607, gxhash32_keyed(&[0u8; 0], 0, &secret));
+        assert_eq!(3815449382, gxhash32_keyed(&[0u8; 1], 0, &secret));
+        assert_eq!(3090877834, gxhash32_keyed(&[0u8; 1000], 0, &secret));
+        assert_eq!(963739814, gxhash32_keyed(&[42u8; 4242], 42, &secret));
+        assert_eq!(3750057697, gxhash32_keyed(b"Hello World", i64::MAX, &GxSecret::from_key256([42u8; 32])));
+    }
+
+    #[test]
+    fn secret_debug_does_not_leak_keys() {
+        assert_eq!("GxSecret { .. }", format!("{:?}", GxSecret::from_key128(42)));
+    }
+}
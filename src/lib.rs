@@ -1,12 +1,48 @@
 #![cfg_attr(not(feature = "std"), no_std)]
-// Hybrid SIMD width usage currently requires unstable 'stdsimd'
-#![cfg_attr(feature = "hybrid", feature(stdarch_x86_avx512))]
+// Hybrid SIMD width usage requires unstable 'stdarch_x86_avx512' before Rust 1.89 (see build.rs)
+#![cfg_attr(all(feature = "hybrid", not(stable_vaes)), feature(stdarch_x86_avx512))]
+
+// Cargo unifies features, so a crate enabling it would silently disable random seeding for the whole process
+#[cfg(feature = "deterministic")]
+compile_error!{"The deterministic feature was removed in gxhash 4.0. Use GxBuildHasher::deterministic(), or the GXHASH_SEED environment variable to fix the seed of GxBuildHasher::default() for a whole process."}
 
 #[rustfmt::skip]
 mod gxhash;
 pub use crate::gxhash::*;
 
-#[cfg(feature = "std")]
+mod stream;
+pub use crate::stream::*;
+
+mod keyed;
+pub use crate::keyed::*;
+
+mod combine;
+pub use crate::combine::*;
+
+mod integer;
+pub use crate::integer::*;
+
+mod fixed;
+pub use crate::fixed::*;
+
+mod padded;
+pub use crate::padded::*;
+
+mod batch;
+pub use crate::batch::*;
+
+pub mod columnar;
+
+mod tree;
+pub use crate::tree::*;
+
 mod hasher;
+pub use crate::hasher::*;
+
+mod entropy;
+pub use crate::entropy::*;
+
+#[cfg(feature = "std")]
+mod multiset;
 #[cfg(feature = "std")]
-pub use crate::hasher::*;
\ No newline at end of file
+pub use crate::multiset::*;
\ No newline at end of file
@@ -0,0 +1,286 @@
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use crate::{combine_many, GxBuildHasher};
+
+/// An incremental hash of a multiset (an unordered collection that may contain duplicates).
+///
+/// Each element is hashed to 256 bits (extending its 128-bit hash, see [`GxHasher::finish_into`](crate::GxHasher::finish_into)),
+/// and element hashes are summed modulo 2<sup>256</sup>. The result is thus independent of the insertion order,
+/// and inserting, removing or merging are O(1) with regard to the number of elements already hashed.
+///
+/// Multiset hashes are only comparable when built with the same seed.
+///
+/// # Example
+///
+/// ```
+/// use gxhash::MultisetHash;
+///
+/// let mut a = MultisetHash::new();
+/// a.insert("hello");
+/// a.insert("world");
+///
+/// let mut b = MultisetHash::new();
+/// b.insert("world");
+/// b.insert("hello");
+///
+/// assert_eq!(a.value(), b.value());
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultisetHash {
+    build_hasher: GxBuildHasher,
+    sum: [u128; 2],
+    count: u64,
+}
+
+impl MultisetHash {
+    /// Creates a new, empty multiset hash with a fixed seed, so that values can be persisted or compared
+    /// between processes.
+    ///
+    /// # Warning ⚠️
+    /// A fixed seed makes it possible to craft multisets with colliding hashes.
+    /// Use [`MultisetHash::with_hasher`] with a randomized [`GxBuildHasher`] when hashing untrusted input.
+    #[inline]
+    pub fn new() -> MultisetHash {
+        MultisetHash::with_seed(0)
+    }
+
+    /// Creates a new, empty multiset hash using the provided seed.
+    #[inline]
+    pub fn with_seed(seed: i64) -> MultisetHash {
+        MultisetHash::with_hasher(GxBuildHasher::with_seed(seed))
+    }
+
+    /// Creates a new, empty multiset hash hashing elements with hashers built by the provided builder.
+    #[inline]
+    pub fn with_hasher(build_hasher: GxBuildHasher) -> MultisetHash {
+        MultisetHash { build_hasher, sum: [0; 2], count: 0 }
+    }
+
+    /// Adds an element to the multiset.
+    #[inline]
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let hash = self.hash_item(item);
+        self.add(hash, 1);
+    }
+
+    /// Removes an element from the multiset. Removing an element that was never inserted is allowed,
+    /// and is undone by inserting it.
+    #[inline]
+    pub fn remove<T: Hash + ?Sized>(&mut self, item: &T) {
+        let [lo, hi] = self.hash_item(item);
+        // Subtracting is adding the two's complement
+        let (lo, carry) = (!lo).overflowing_add(1);
+        let hi = (!hi).wrapping_add(carry as u128);
+        self.add([lo, hi], u64::MAX);
+    }
+
+    /// Adds all elements of another multiset to this one.
+    ///
+    /// Both multiset hashes must have been built with the same seed.
+    #[inline]
+    pub fn merge(&mut self, other: &MultisetHash) {
+        debug_assert!(self.build_hasher.seed() == other.build_hasher.seed(), "Multiset hashes must be built with the same seed to be merged");
+        self.add(other.sum, other.count);
+    }
+
+    /// Returns the number of elements in the multiset.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns true if the multiset contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the hash of the multiset.
+    #[inline]
+    pub fn value(&self) -> u128 {
+        combine_many(&[self.sum[0], self.sum[1], self.count as u128])
+    }
+
+    #[inline]
+    fn hash_item<T: Hash + ?Sized>(&self, item: &T) -> [u128; 2] {
+        let mut hasher = self.build_hasher.build_hasher();
+        item.hash(&mut hasher);
+        let mut digest = [0u8; 32];
+        hasher.finish_into(&mut digest);
+        let mut lo = [0u8; 16];
+        let mut hi = [0u8; 16];
+        lo.copy_from_slice(&digest[..16]);
+        hi.copy_from_slice(&digest[16..]);
+        [u128::from_le_bytes(lo), u128::from_le_bytes(hi)]
+    }
+
+    #[inline]
+    fn add(&mut self, [lo, hi]: [u128; 2], count: u64) {
+        let (lo, carry) = self.sum[0].overflowing_add(lo);
+        self.sum[0] = lo;
+        self.sum[1] = self.sum[1].wrapping_add(hi).wrapping_add(carry as u128);
+        self.count = self.count.wrapping_add(count);
+    }
+}
+
+impl Default for MultisetHash {
+    #[inline]
+    fn default() -> MultisetHash {
+        MultisetHash::new()
+    }
+}
+
+impl<'a, T: Hash + ?Sized + 'a> Extend<&'a T> for MultisetHash {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+/// A wrapper for hashing unordered collections such as `HashSet` or `HashMap` independently of their
+/// iteration order, through a [`MultisetHash`].
+///
+/// Elements are hashed into a [`MultisetHash`] built with the wrapper's [`GxBuildHasher`], and the multiset
+/// hash is then written to the outer [`Hasher`].
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use std::hash::BuildHasher;
+/// use gxhash::{GxBuildHasher, Unordered};
+///
+/// let a: HashSet<u32> = (0..100).collect();
+/// let b: HashSet<u32> = (0..100).rev().collect();
+///
+/// let build_hasher = GxBuildHasher::default();
+/// let elements = GxBuildHasher::default();
+/// assert_eq!(build_hasher.hash_one(Unordered::with_hasher(&a, elements.clone())),
+///            build_hasher.hash_one(Unordered::with_hasher(&b, elements)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Unordered<'a, C: ?Sized> {
+    collection: &'a C,
+    build_hasher: GxBuildHasher,
+}
+
+impl<'a, C: ?Sized> Unordered<'a, C> {
+    /// Wraps a collection, hashing its elements with a fixed seed (see [`MultisetHash::new`]).
+    ///
+    /// # Warning ⚠️
+    /// The outer [`Hasher`] only sees the multiset hash, so its seed does not protect against collections
+    /// crafted to collide under the fixed seed of the elements.
+    /// Use [`Unordered::with_hasher`] with a randomized [`GxBuildHasher`] when hashing untrusted input.
+    #[inline]
+    pub fn new(collection: &'a C) -> Unordered<'a, C> {
+        Unordered::with_hasher(collection, GxBuildHasher::with_seed(0))
+    }
+
+    /// Wraps a collection, hashing its elements with hashers built by the provided builder.
+    ///
+    /// Hashes of the wrapper are only comparable when built with the same builder.
+    #[inline]
+    pub fn with_hasher(collection: &'a C, build_hasher: GxBuildHasher) -> Unordered<'a, C> {
+        Unordered { collection, build_hasher }
+    }
+}
+
+impl<'a, C: ?Sized> Hash for Unordered<'a, C>
+    where &'a C: IntoIterator,
+    <&'a C as IntoIterator>::Item: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut multiset = MultisetHash::with_hasher(self.build_hasher.clone());
+        for item in self.collection {
+            multiset.insert(&item);
+        }
+        state.write_u128(multiset.value());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+
+    #[test]
+    fn multiset_is_order_independent() {
+        let mut a = MultisetHash::new();
+        let mut b = MultisetHash::new();
+        a.extend(&[1, 2, 3, 3]);
+        b.extend(&[3, 1, 3, 2]);
+        assert_eq!(a.value(), b.value());
+        assert_eq!(4, a.len());
+
+        b.insert(&3);
+        assert_ne!(a.value(), b.value());
+    }
+
+    #[test]
+    fn multiset_remove_undoes_insert() {
+        let mut a = MultisetHash::new();
+        a.extend(&["hello", "world"]);
+        let value = a.value();
+
+        a.insert("foo");
+        assert_ne!(value, a.value());
+        a.remove("foo");
+        assert_eq!(value, a.value());
+
+        let mut empty = MultisetHash::new();
+        empty.remove("foo");
+        empty.insert("foo");
+        assert_eq!(MultisetHash::new().value(), empty.value());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn multiset_merge_is_union() {
+        let mut a = MultisetHash::with_seed(42);
+        let mut b = MultisetHash::with_seed(42);
+        let mut union = MultisetHash::with_seed(42);
+        a.extend(&[1u64, 2, 3]);
+        b.extend(&[3u64, 4]);
+        union.extend(&[4u64, 3, 3, 2, 1]);
+        a.merge(&b);
+        assert_eq!(union.value(), a.value());
+    }
+
+    #[test]
+    fn multiset_depends_on_seed() {
+        let mut a = MultisetHash::with_seed(1);
+        let mut b = MultisetHash::with_seed(2);
+        a.insert("hello");
+        b.insert("hello");
+        assert_ne!(a.value(), b.value());
+    }
+
+    #[test]
+    fn unordered_hashes_std_collections() {
+        let a: HashSet<u32, GxBuildHasher> = (0..1000).collect();
+        let mut b: HashSet<u32, GxBuildHasher> = (0..1000).rev().collect();
+        let build_hasher = GxBuildHasher::with_seed(0);
+        assert_eq!(build_hasher.hash_one(Unordered::new(&a)), build_hasher.hash_one(Unordered::new(&b)));
+
+        b.remove(&42);
+        assert_ne!(build_hasher.hash_one(Unordered::new(&a)), build_hasher.hash_one(Unordered::new(&b)));
+
+        let a: HashMap<&str, u32> = [("a", 1), ("b", 2)].into_iter().collect();
+        let b: HashMap<&str, u32> = [("b", 2), ("a", 1)].into_iter().collect();
+        let c: HashMap<&str, u32> = [("a", 2), ("b", 1)].into_iter().collect();
+        assert_eq!(build_hasher.hash_one(Unordered::new(&a)), build_hasher.hash_one(Unordered::new(&b)));
+        assert_ne!(build_hasher.hash_one(Unordered::new(&a)), build_hasher.hash_one(Unordered::new(&c)));
+    }
+
+    #[test]
+    fn unordered_depends_on_element_seed() {
+        let a: HashSet<u32> = (0..100).collect();
+        let build_hasher = GxBuildHasher::with_seed(0);
+        let hash = |seed| build_hasher.hash_one(Unordered::with_hasher(&a, GxBuildHasher::with_seed(seed)));
+        assert_eq!(build_hasher.hash_one(Unordered::new(&a)), hash(0));
+        assert_ne!(hash(1), hash(2));
+    }
+}
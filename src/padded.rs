@@ -0,0 +1,146 @@
+use crate::gxhash::platform::*;
+use crate::gxhash::*;
+
+/// An input followed by at least [`PaddedBytes::PADDING`] readable bytes, such as a key stored in an
+/// arena that leaves some slack after each entry.
+///
+/// Small inputs are usually loaded with a single SIMD load, reading a few bytes beyond the end of the input
+/// and ignoring them. Since reading beyond the end of an allocation may fault, [`gxhash64`] and others first
+/// check whether the bytes beyond are on the same memory page, and copy the input otherwise. With padded
+/// inputs, the trailing bytes are known to be readable, so [`gxhash64_padded`] and others always use the
+/// single load. Only the first `len` bytes are hashed: hashes are the same as for the unpadded input.
+///
+/// # Example
+///
+/// ```
+/// use gxhash::PaddedBytes;
+///
+/// let buffer = [42u8; 64];
+/// let input = PaddedBytes::new(&buffer, 10);
+/// assert_eq!(gxhash::gxhash64(&buffer[..10], 1234), gxhash::gxhash64_padded(input, 1234));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PaddedBytes<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> PaddedBytes<'a> {
+    /// Number of readable bytes that must follow the input.
+    pub const PADDING: usize = VECTOR_SIZE;
+
+    /// Creates an input from the first `len` bytes of the buffer, the rest of the buffer being the padding.
+    ///
+    /// # Panics
+    /// Panics if the buffer is shorter than `len + PADDING` bytes.
+    #[inline]
+    pub fn new(buffer: &'a [u8], len: usize) -> PaddedBytes<'a> {
+        assert!(len <= buffer.len().saturating_sub(Self::PADDING), "Buffer must have at least {} bytes of padding after the input", Self::PADDING);
+        PaddedBytes { input: &buffer[..len] }
+    }
+
+    /// Same as [`PaddedBytes::new`], without checking the padding in release builds.
+    ///
+    /// # Safety
+    /// The buffer must be at least `len + PADDING` bytes long. This is checked in debug builds only.
+    #[inline]
+    pub unsafe fn new_unchecked(buffer: &'a [u8], len: usize) -> PaddedBytes<'a> {
+        debug_assert!(len <= buffer.len().saturating_sub(Self::PADDING), "Buffer must have at least {} bytes of padding after the input", Self::PADDING);
+        PaddedBytes { input: buffer.get_unchecked(..len) }
+    }
+
+    /// The input, without the padding.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.input
+    }
+}
+
+/// Hashes a padded input to an u32. The hash is the same as [`gxhash32`] on the unpadded input.
+///
+/// # Example
+///
+/// ```
+/// let buffer = [42u8; 64];
+/// println!("Hash is {:x}!", gxhash::gxhash32_padded(gxhash::PaddedBytes::new(&buffer, 10), 1234));
+/// ```
+#[inline(always)]
+pub fn gxhash32_padded(input: PaddedBytes, seed: i64) -> u32 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_padded::<B>(input, create_seed(seed)) as *const State as *const u32;
+        u32::from_le(*p)
+    })
+}
+
+/// Hashes a padded input to an u64. The hash is the same as [`gxhash64`] on the unpadded input.
+///
+/// # Example
+///
+/// ```
+/// let buffer = [42u8; 64];
+/// println!("Hash is {:x}!", gxhash::gxhash64_padded(gxhash::PaddedBytes::new(&buffer, 10), 1234));
+/// ```
+#[inline(always)]
+pub fn gxhash64_padded(input: PaddedBytes, seed: i64) -> u64 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_padded::<B>(input, create_seed(seed)) as *const State as *const u64;
+        u64::from_le(*p)
+    })
+}
+
+/// Hashes a padded input to an u128. The hash is the same as [`gxhash128`] on the unpadded input.
+///
+/// # Example
+///
+/// ```
+/// let buffer = [42u8; 64];
+/// println!("Hash is {:x}!", gxhash::gxhash128_padded(gxhash::PaddedBytes::new(&buffer, 10), 1234));
+/// ```
+#[inline(always)]
+pub fn gxhash128_padded(input: PaddedBytes, seed: i64) -> u128 {
+    dispatch!(B => unsafe {
+        let p = &gxhash_padded::<B>(input, create_seed(seed)) as *const State as *const u128;
+        u128::from_le(*p)
+    })
+}
+
+#[inline(always)]
+unsafe fn gxhash_padded<B: Aes>(input: PaddedBytes, seed: State) -> State {
+    let input = input.as_bytes();
+    let len = input.len();
+    let compressed = if len != 0 && len <= VECTOR_SIZE {
+        // Same as in compress_all_with, minus the page check
+        get_partial_unsafe(input.as_ptr() as *const State, len)
+    } else {
+        compress_all_with::<B>(input, &KEYS, Profile::Default)
+    };
+    finalize_with::<B>(B::aes_encrypt(compressed, seed), &KEYS, Profile::Default)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn padded_hashes_are_unpadded_hashes() {
+        let mut buffer = [0u8; 300];
+        rand::thread_rng().fill(buffer.as_mut_slice());
+
+        for offset in 0..VECTOR_SIZE {
+            for len in 0..=buffer.len() - offset - PaddedBytes::PADDING {
+                let padded = PaddedBytes::new(&buffer[offset..], len);
+                let input = &buffer[offset..offset + len];
+                assert_eq!(gxhash32(input, 42), gxhash32_padded(padded, 42), "offset {offset}, len {len}");
+                assert_eq!(gxhash64(input, 42), gxhash64_padded(padded, 42), "offset {offset}, len {len}");
+                assert_eq!(gxhash128(input, 42), gxhash128_padded(padded, 42), "offset {offset}, len {len}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn missing_padding_panics() {
+        PaddedBytes::new(&[0u8; 20], 5);
+    }
+}
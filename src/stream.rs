@@ -0,0 +1,499 @@
+use core::cmp::min;
+
+use crate::gxhash::platform::*;
+use crate::gxhash::*;
+
+const GROUP_SIZE: usize = VECTOR_SIZE * 8;
+
+// Version of the serialized stream format, to be bumped whenever the layout changes
+const SERIALIZATION_VERSION: u8 = 1;
+
+/// A streaming hasher for hashing an input that arrives in chunks, such as a network payload
+/// or a file body.
+///
+/// Unlike [`GxHasher`](crate::GxHasher), the hash produced by [`GxStream`] is bit-identical to
+/// [`gxhash32`], [`gxhash64`] and [`gxhash128`] over the concatenated input, whatever the chunking.
+///
+/// GxHash reads the `len % 16` leading bytes first, so that all subsequent vectors are aligned on
+/// the end of the input. For this reason the total length of the input must be known upfront.
+/// Only partial vectors and 8 × 16-byte blocks are buffered, so memory usage is constant.
+///
+/// A stream can be checkpointed with [`GxStream::to_bytes`] and resumed with [`GxStream::from_bytes`],
+/// possibly in another process or on another supported architecture.
+///
+/// # Example
+///
+/// ```
+/// use gxhash::GxStream;
+///
+/// let bytes = [42u8; 1000];
+/// let mut stream = GxStream::new(bytes.len(), 1234);
+///
+/// for chunk in bytes.chunks(100) {
+///     stream.update(chunk);
+/// }
+///
+/// assert_eq!(stream.finish64(), gxhash::gxhash64(&bytes, 1234));
+/// ```
+#[derive(Clone, Debug)]
+pub struct GxStream {
+    seed: State,
+    len: usize,
+    processed: usize,
+    hash_vector: State,
+    v0: State,
+    lane1: State,
+    lane2: State,
+    t1: State,
+    t2: State,
+    buffer: [u8; GROUP_SIZE],
+    buffered: usize,
+}
+
+impl GxStream {
+    /// Creates a new stream for an input of exactly `len` bytes, using the provided seed.
+    #[inline]
+    pub fn new(len: usize, seed: i64) -> GxStream {
+        GxStream::with_state(len, unsafe { create_seed(seed) })
+    }
+
+    /// Creates a new stream for an input of exactly `len` bytes, using the provided 128-bit seed.
+    /// The output is the same as [`gxhash128_seeded`](crate::gxhash128_seeded).
+    #[inline]
+    pub fn with_seed128(len: usize, seed: u128) -> GxStream {
+        GxStream::with_state(len, dispatch!(B => unsafe { create_seed128::<B>(seed) }))
+    }
+
+    #[inline]
+    fn with_state(len: usize, seed: State) -> GxStream {
+        unsafe {
+            GxStream {
+                seed,
+                len,
+                processed: 0,
+                hash_vector: create_empty(),
+                v0: create_empty(),
+                lane1: create_empty(),
+                lane2: create_empty(),
+                t1: create_empty(),
+                t2: create_empty(),
+                buffer: [0; GROUP_SIZE],
+                buffered: 0,
+            }
+        }
+    }
+
+    /// Feeds the next chunk of the input.
+    ///
+    /// # Panics
+    /// Panics if more bytes than the length given at construction are fed.
+    pub fn update(&mut self, mut bytes: &[u8]) {
+        assert!(bytes.len() <= self.len - self.processed - self.buffered, "More bytes were fed to the stream than its declared length");
+
+        dispatch!(B => while !bytes.is_empty() {
+            let unit_len = self.unit_len();
+            if self.buffered == 0 && bytes.len() >= unit_len {
+                // Enough bytes for a whole unit, we can read directly from the input
+                unsafe { self.absorb::<B>(bytes.as_ptr() as *const State) };
+                bytes = &bytes[unit_len..];
+            } else {
+                let count = min(unit_len - self.buffered, bytes.len());
+                self.buffer[self.buffered..self.buffered + count].copy_from_slice(&bytes[..count]);
+                self.buffered += count;
+                bytes = &bytes[count..];
+                if self.buffered == unit_len {
+                    unsafe { self.absorb::<B>(self.buffer.as_ptr() as *const State) };
+                    self.buffered = 0;
+                }
+            }
+        })
+    }
+
+    /// Size in bytes of a serialized stream.
+    pub const SERIALIZED_SIZE: usize = 1 + 8 + 8 + 1 + VECTOR_SIZE * 7 + GROUP_SIZE;
+
+    /// Serializes the stream mid-way, including its buffered bytes, so that hashing can be resumed later
+    /// with [`GxStream::from_bytes`]. The format is the same on all supported platforms.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gxhash::GxStream;
+    ///
+    /// let bytes = [42u8; 1000];
+    /// let mut stream = GxStream::new(bytes.len(), 1234);
+    /// stream.update(&bytes[..500]);
+    ///
+    /// let checkpoint = stream.to_bytes();
+    ///
+    /// let mut stream = GxStream::from_bytes(&checkpoint).unwrap();
+    /// stream.update(&bytes[500..]);
+    ///
+    /// assert_eq!(stream.finish64(), gxhash::gxhash64(&bytes, 1234));
+    /// ```
+    pub fn to_bytes(&self) -> [u8; GxStream::SERIALIZED_SIZE] {
+        let mut bytes = [0u8; GxStream::SERIALIZED_SIZE];
+        bytes[0] = SERIALIZATION_VERSION;
+        bytes[1..9].copy_from_slice(&(self.len as u64).to_le_bytes());
+        bytes[9..17].copy_from_slice(&(self.processed as u64).to_le_bytes());
+        bytes[17] = self.buffered as u8;
+        let states = [self.seed, self.hash_vector, self.v0, self.lane1, self.lane2, self.t1, self.t2];
+        for (chunk, state) in bytes[18..18 + VECTOR_SIZE * 7].chunks_exact_mut(VECTOR_SIZE).zip(states) {
+            chunk.copy_from_slice(&unsafe { core::mem::transmute::<State, [u8; VECTOR_SIZE]>(state) });
+        }
+        bytes[18 + VECTOR_SIZE * 7..].copy_from_slice(&self.buffer);
+        bytes
+    }
+
+    /// Restores a stream serialized with [`GxStream::to_bytes`].
+    ///
+    /// Returns `None` if the bytes are not a valid serialized stream.
+    pub fn from_bytes(bytes: &[u8]) -> Option<GxStream> {
+        if bytes.len() != GxStream::SERIALIZED_SIZE || bytes[0] != SERIALIZATION_VERSION {
+            return None;
+        }
+
+        let read_usize = |range: core::ops::Range<usize>| {
+            let mut le_bytes = [0u8; 8];
+            le_bytes.copy_from_slice(&bytes[range]);
+            usize::try_from(u64::from_le_bytes(le_bytes)).ok()
+        };
+        let read_state = |i: usize| unsafe { load_unaligned(bytes[18 + i * VECTOR_SIZE..].as_ptr() as *const State) };
+
+        let mut stream = GxStream::with_state(read_usize(1..9)?, read_state(0));
+        stream.processed = read_usize(9..17)?;
+        stream.buffered = bytes[17] as usize;
+        stream.hash_vector = read_state(1);
+        stream.v0 = read_state(2);
+        stream.lane1 = read_state(3);
+        stream.lane2 = read_state(4);
+        stream.t1 = read_state(5);
+        stream.t2 = read_state(6);
+        stream.buffer.copy_from_slice(&bytes[18 + VECTOR_SIZE * 7..]);
+
+        if stream.processed > stream.len || !stream.is_unit_boundary() || stream.buffered > stream.len - stream.processed
+            || (stream.processed < stream.len && stream.buffered >= stream.unit_len()) {
+            return None;
+        }
+
+        Some(stream)
+    }
+
+    /// Returns the number of bytes that remain to be fed before the stream can be finished.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.len - self.processed - self.buffered
+    }
+
+    /// Finishes the stream and returns the hash as an u32.
+    ///
+    /// # Panics
+    /// Panics if fewer bytes than the length given at construction were fed.
+    #[inline]
+    pub fn finish32(&self) -> u32 {
+        dispatch!(B => unsafe {
+            let p = &self.finish::<B>() as *const State as *const u32;
+            u32::from_le(*p)
+        })
+    }
+
+    /// Finishes the stream and returns the hash as an u64.
+    ///
+    /// # Panics
+    /// Panics if fewer bytes than the length given at construction were fed.
+    #[inline]
+    pub fn finish64(&self) -> u64 {
+        dispatch!(B => unsafe {
+            let p = &self.finish::<B>() as *const State as *const u64;
+            u64::from_le(*p)
+        })
+    }
+
+    /// Finishes the stream and returns the hash as an u128.
+    ///
+    /// # Panics
+    /// Panics if fewer bytes than the length given at construction were fed.
+    #[inline]
+    pub fn finish128(&self) -> u128 {
+        dispatch!(B => unsafe {
+            let p = &self.finish::<B>() as *const State as *const u128;
+            u128::from_le(*p)
+        })
+    }
+
+    // The input is consumed in the same units as `compress_all`: the leading partial vector (or the
+    // whole input if it fits in a single vector), then up to 3 vectors folded in `v0`, then single
+    // vectors folded in the hash vector until a whole number of 8 vectors blocks remain.
+    #[inline(always)]
+    fn head_len(&self) -> usize {
+        match self.len % VECTOR_SIZE {
+            0 => VECTOR_SIZE,
+            extra_bytes_count => extra_bytes_count,
+        }
+    }
+
+    #[inline(always)]
+    fn groups_start(&self) -> usize {
+        if self.len <= VECTOR_SIZE * 4 {
+            return self.len;
+        }
+        let remaining_bytes = self.len - self.head_len() - VECTOR_SIZE * 3;
+        self.len - remaining_bytes / GROUP_SIZE * GROUP_SIZE
+    }
+
+    #[inline(always)]
+    fn unit_len(&self) -> usize {
+        if self.len <= VECTOR_SIZE {
+            self.len
+        } else if self.processed == 0 {
+            self.head_len()
+        } else if self.processed < self.groups_start() {
+            VECTOR_SIZE
+        } else {
+            GROUP_SIZE
+        }
+    }
+
+    // Whether the processed length is one that feeding the stream can reach, that is the end of a unit
+    // (usize::is_multiple_of requires Rust 1.87)
+    #[allow(clippy::manual_is_multiple_of)]
+    fn is_unit_boundary(&self) -> bool {
+        if self.processed == 0 || self.len <= VECTOR_SIZE {
+            return self.processed == 0 || self.processed == self.len;
+        }
+        let head_len = self.head_len();
+        let groups_start = self.groups_start();
+        if self.processed < head_len {
+            false
+        } else if self.processed <= groups_start {
+            (self.processed - head_len) % VECTOR_SIZE == 0
+        } else {
+            (self.processed - groups_start) % GROUP_SIZE == 0
+        }
+    }
+
+    #[inline(always)]
+    #[allow(unused_assignments)]
+    unsafe fn absorb<B: Aes>(&mut self, mut ptr: *const State) {
+        if self.len <= VECTOR_SIZE {
+            self.hash_vector = get_partial_safe(ptr, self.len);
+            self.processed = self.len;
+            return;
+        }
+
+        let head_len = self.head_len();
+        let groups_start = self.groups_start();
+
+        if self.processed == 0 {
+            self.hash_vector = if head_len == VECTOR_SIZE { load_unaligned(ptr) } else { get_partial_safe(ptr, head_len) };
+            self.processed = head_len;
+        } else if self.processed == head_len {
+            self.v0 = load_unaligned(ptr);
+            self.processed += VECTOR_SIZE;
+        } else if self.processed < head_len + VECTOR_SIZE * 3 {
+            self.v0 = B::aes_encrypt(self.v0, load_unaligned(ptr));
+            self.processed += VECTOR_SIZE;
+        } else if self.processed < groups_start {
+            self.hash_vector = B::aes_encrypt(self.hash_vector, load_unaligned(ptr));
+            self.processed += VECTOR_SIZE;
+        } else {
+            if self.processed == groups_start {
+                self.lane1 = self.hash_vector;
+                self.lane2 = self.hash_vector;
+            }
+
+            load_unaligned!(ptr, v0, v1, v2, v3, v4, v5, v6, v7);
+
+            let mut tmp1 = B::aes_encrypt(v0, v2);
+            let mut tmp2 = B::aes_encrypt(v1, v3);
+
+            tmp1 = B::aes_encrypt(tmp1, v4);
+            tmp2 = B::aes_encrypt(tmp2, v5);
+
+            tmp1 = B::aes_encrypt(tmp1, v6);
+            tmp2 = B::aes_encrypt(tmp2, v7);
+
+            self.t1 = add_bytes(self.t1, ld(KEYS.as_ptr()));
+            self.t2 = add_bytes(self.t2, ld(KEYS.as_ptr().offset(4)));
+
+            self.lane1 = B::aes_encrypt_last(B::aes_encrypt(tmp1, self.t1), self.lane1);
+            self.lane2 = B::aes_encrypt_last(B::aes_encrypt(tmp2, self.t2), self.lane2);
+            self.processed += GROUP_SIZE;
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn compress<B: Aes>(&self) -> State {
+        assert!(self.processed == self.len, "Fewer bytes were fed to the stream than its declared length");
+
+        if self.len == 0 {
+            return create_empty();
+        }
+
+        if self.len <= VECTOR_SIZE {
+            return self.hash_vector;
+        }
+
+        let mut hash_vector = self.hash_vector;
+
+        if self.len > VECTOR_SIZE * 4 {
+            let (mut lane1, mut lane2) = if self.groups_start() == self.len {
+                (hash_vector, hash_vector)
+            } else {
+                (self.lane1, self.lane2)
+            };
+            // Same as the end of `compress_8`
+            let len_vec = load_u32(self.len as u32);
+            lane1 = add_bytes(lane1, len_vec);
+            lane2 = add_bytes(lane2, len_vec);
+            hash_vector = B::aes_encrypt(lane1, lane2);
+        }
+
+        B::aes_encrypt_last(hash_vector,
+            B::aes_encrypt(B::aes_encrypt(self.v0, ld(KEYS.as_ptr())), ld(KEYS.as_ptr().offset(4))))
+    }
+
+    #[inline(always)]
+    unsafe fn finish<B: Aes>(&self) -> State {
+        finalize::<B>(B::aes_encrypt(self.compress::<B>(), self.seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn stream_matches_gxhash_for_any_chunking() {
+        let mut bytes = [0u8; 1200];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+
+        for len in 0..bytes.len() {
+            let input = &bytes[..len];
+            let expected = gxhash128(input, 42);
+
+            for chunk_size in [1, 3, 15, 16, 17, 64, 127, 128, 129, 1000] {
+                let mut stream = GxStream::new(len, 42);
+                for chunk in input.chunks(chunk_size) {
+                    stream.update(chunk);
+                }
+                assert_eq!(expected, stream.finish128(), "Hash mismatch for input of size {len} in chunks of {chunk_size}");
+            }
+        }
+    }
+
+    #[test]
+    fn stream_matches_gxhash_for_random_chunking() {
+        let mut rng = rand::thread_rng();
+        let mut bytes = vec![0u8; 10_000];
+        rng.fill(bytes.as_mut_slice());
+
+        for _ in 0..100 {
+            let len = rng.gen_range(0..bytes.len());
+            let input = &bytes[..len];
+            let mut stream = GxStream::new(len, -1);
+            let mut rest = input;
+            while !rest.is_empty() {
+                let (chunk, tail) = rest.split_at(rng.gen_range(0..=rest.len().min(300)));
+                stream.update(chunk);
+                rest = tail;
+            }
+            assert_eq!(gxhash32(input, -1), stream.finish32());
+            assert_eq!(gxhash64(input, -1), stream.finish64());
+            assert_eq!(gxhash128(input, -1), stream.finish128());
+        }
+    }
+
+    #[test]
+    fn stream_matches_gxhash_seeded() {
+        let bytes = [42u8; 300];
+        let mut stream = GxStream::with_seed128(bytes.len(), u128::MAX);
+        stream.update(&bytes);
+        assert_eq!(gxhash128_seeded(&bytes, u128::MAX), stream.finish128());
+    }
+
+    #[test]
+    fn stream_can_be_resumed_from_bytes() {
+        let mut bytes = [0u8; 1200];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+
+        for len in [0, 1, 16, 17, 63, 64, 65, 200, 1200] {
+            let input = &bytes[..len];
+            for split in (0..=len).step_by(7) {
+                let mut stream = GxStream::new(len, 42);
+                stream.update(&input[..split]);
+
+                let mut resumed = GxStream::from_bytes(&stream.to_bytes()).unwrap();
+                resumed.update(&input[split..]);
+                assert_eq!(gxhash128(input, 42), resumed.finish128(), "Hash mismatch for input of size {len} resumed at {split}");
+            }
+        }
+    }
+
+    #[test]
+    fn stream_serialization_is_stable() {
+        let mut stream = GxStream::new(100, 42);
+        stream.update(&[42u8; 70]);
+        let bytes = stream.to_bytes();
+        assert_eq!(&bytes[..18], &[1, 100, 0, 0, 0, 0, 0, 0, 0, 68, 0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(gxhash128(&bytes, 0), 284951338254636693468766291955734780320);
+    }
+
+    #[test]
+    fn stream_rejects_invalid_bytes() {
+        let bytes = GxStream::new(100, 42).to_bytes();
+        assert!(GxStream::from_bytes(&bytes[1..]).is_none());
+
+        let mut invalid = bytes;
+        invalid[0] = 0;
+        assert!(GxStream::from_bytes(&invalid).is_none());
+
+        let mut invalid = bytes;
+        invalid[9] = 101;
+        assert!(GxStream::from_bytes(&invalid).is_none());
+
+        let mut invalid = bytes;
+        invalid[17] = 16;
+        assert!(GxStream::from_bytes(&invalid).is_none());
+    }
+
+    #[test]
+    fn stream_rejects_unreachable_positions() {
+        let bytes = [42u8; 1000];
+        let mut stream = GxStream::new(bytes.len(), 42);
+        stream.update(&bytes[..104]);
+        let checkpoint = stream.to_bytes();
+        assert!(GxStream::from_bytes(&checkpoint).is_some());
+
+        // Not the end of a unit, for the leading partial vector, single vectors and blocks of 8 vectors
+        for processed in [5, 109, 104 + VECTOR_SIZE * 3 + 8, 1000 - GROUP_SIZE - VECTOR_SIZE] {
+            let mut invalid = checkpoint;
+            invalid[9..17].copy_from_slice(&(processed as u64).to_le_bytes());
+            assert!(GxStream::from_bytes(&invalid).is_none(), "Position {processed} should be rejected");
+        }
+
+        // Positions reached by feeding the stream in units
+        let mut stream = GxStream::new(bytes.len(), 42);
+        for _ in 0..bytes.len() {
+            stream.update(&bytes[..1]);
+            assert!(GxStream::from_bytes(&stream.to_bytes()).is_some());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn stream_rejects_extra_bytes() {
+        let mut stream = GxStream::new(10, 0);
+        stream.update(&[0u8; 11]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn stream_rejects_early_finish() {
+        let mut stream = GxStream::new(10, 0);
+        stream.update(&[0u8; 9]);
+        stream.finish64();
+    }
+}
@@ -0,0 +1,213 @@
+use crate::combine::combine_states;
+use crate::gxhash::platform::*;
+use crate::gxhash::*;
+
+/// Version of the tree hashing mode. Any change to the tree shape, to the way leaves are hashed or to the
+/// way nodes are combined is a new version, so that tree hashes can be persisted and compared.
+pub const TREE_VERSION: u32 = 1;
+
+/// Hashes an arbitrary stream of bytes to an u128 in tree mode, so that large buffers can be hashed in parallel.
+///
+/// The input is split in leaves of `chunk_size` bytes (the last leaf may be shorter), which are hashed
+/// independently with their index mixed in the seed. Leaves are then combined pairwise, left to right,
+/// level by level (a node left without a sibling is promoted to the next level as is), and the root is
+/// combined with the input length and the chunk size.
+///
+/// With the `std` feature, leaves are hashed on all available cores. The result does not depend on the
+/// number of threads, and is the same as [`gxhash_tree128_sequential`].
+/// Tree hashes are not the same as [`gxhash128`], and depend on the chunk size.
+///
+/// # Panics
+/// Panics if `chunk_size` is 0.
+///
+/// # Example
+///
+/// ```
+/// let bytes = vec![42u8; 1 << 20];
+/// let hash = gxhash::gxhash_tree128(&bytes, 1234, 64 * 1024);
+/// assert_eq!(hash, gxhash::gxhash_tree128_sequential(&bytes, 1234, 64 * 1024));
+/// ```
+#[inline]
+pub fn gxhash_tree128(input: &[u8], seed: i64, chunk_size: usize) -> u128 {
+    #[cfg(feature = "std")]
+    {
+        let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        gxhash_tree128_with_threads(input, seed, chunk_size, threads)
+    }
+    #[cfg(not(feature = "std"))]
+    gxhash_tree128_sequential(input, seed, chunk_size)
+}
+
+/// Hashes an arbitrary stream of bytes to an u128 in tree mode, on a single thread. See [`gxhash_tree128`].
+///
+/// Leaves are combined as they are hashed, without any allocation.
+pub fn gxhash_tree128_sequential(input: &[u8], seed: i64, chunk_size: usize) -> u128 {
+    assert!(chunk_size > 0, "Chunk size must be greater than 0");
+    dispatch!(B => unsafe {
+        let seed = tree_seed::<B>(seed);
+        let mut stack = TreeStack::new();
+        for (index, leaf) in leaves(input, chunk_size).enumerate() {
+            stack.push::<B>(hash_leaf::<B>(leaf, seed, index));
+        }
+        to_u128(stack.root::<B>(input.len(), chunk_size))
+    })
+}
+
+/// Hashes an arbitrary stream of bytes to an u128 in tree mode, with leaves hashed on the given number
+/// of threads. See [`gxhash_tree128`].
+///
+/// # Panics
+/// Panics if `chunk_size` is 0.
+#[cfg(feature = "std")]
+pub fn gxhash_tree128_with_threads(input: &[u8], seed: i64, chunk_size: usize, threads: usize) -> u128 {
+    assert!(chunk_size > 0, "Chunk size must be greater than 0");
+
+    // An empty input is a single empty leaf
+    let leaves_count = 1 + (input.len().max(1) - 1) / chunk_size;
+    let threads = threads.clamp(1, leaves_count);
+    if threads == 1 {
+        return gxhash_tree128_sequential(input, seed, chunk_size);
+    }
+
+    let seed = dispatch!(B => unsafe { tree_seed::<B>(seed) });
+    let mut hashes = vec![unsafe { create_empty() }; leaves_count];
+    let leaves_per_thread = 1 + (leaves_count - 1) / threads;
+
+    std::thread::scope(|scope| {
+        for (i, hashes) in hashes.chunks_mut(leaves_per_thread).enumerate() {
+            let first_leaf = i * leaves_per_thread;
+            let input = &input[first_leaf * chunk_size..];
+            // Each thread runs the copy of the loop compiled for the detected backend
+            scope.spawn(move || dispatch!(B => unsafe {
+                for (j, (hash, leaf)) in hashes.iter_mut().zip(input.chunks(chunk_size)).enumerate() {
+                    *hash = hash_leaf::<B>(leaf, seed, first_leaf + j);
+                }
+            }));
+        }
+    });
+
+    dispatch!(B => unsafe {
+        let mut stack = TreeStack::new();
+        for hash in hashes {
+            stack.push::<B>(hash);
+        }
+        to_u128(stack.root::<B>(input.len(), chunk_size))
+    })
+}
+
+#[inline(always)]
+fn leaves(input: &[u8], chunk_size: usize) -> impl Iterator<Item = &[u8]> {
+    // An empty input is a single empty leaf
+    let empty: &[u8] = &[];
+    input.chunks(chunk_size).chain(core::iter::once(empty).filter(move |_| input.is_empty()))
+}
+
+#[inline(always)]
+unsafe fn tree_seed<B: Aes>(seed: i64) -> State {
+    B::aes_encrypt(create_seed(seed), load_u32(TREE_VERSION))
+}
+
+#[inline(always)]
+unsafe fn hash_leaf<B: Aes>(leaf: &[u8], seed: State, index: usize) -> State {
+    gxhash::<B>(leaf, B::aes_encrypt(seed, load_u64(index as u64)))
+}
+
+#[inline(always)]
+unsafe fn to_u128(state: State) -> u128 {
+    let p = &state as *const State as *const u128;
+    u128::from_le(*p)
+}
+
+/// Combines leaves as they come. Combining pairwise level by level is the same as making the left subtree
+/// of each node the largest complete binary tree, so nodes can be combined as soon as both children are known:
+/// after the n-th leaf is pushed, there are as many pairs to combine as trailing zeros in n.
+struct TreeStack {
+    nodes: [State; 64],
+    len: usize,
+    leaves_count: usize,
+}
+
+impl TreeStack {
+    #[inline(always)]
+    unsafe fn new() -> TreeStack {
+        TreeStack { nodes: [create_empty(); 64], len: 0, leaves_count: 0 }
+    }
+
+    #[inline(always)]
+    unsafe fn push<B: Aes>(&mut self, leaf: State) {
+        self.nodes[self.len] = leaf;
+        self.len += 1;
+        self.leaves_count += 1;
+        for _ in 0..self.leaves_count.trailing_zeros() {
+            self.len -= 1;
+            self.nodes[self.len - 1] = combine_states::<B>(self.nodes[self.len - 1], self.nodes[self.len]);
+        }
+    }
+
+    #[inline(always)]
+    unsafe fn root<B: Aes>(mut self, len: usize, chunk_size: usize) -> State {
+        while self.len > 1 {
+            self.len -= 1;
+            self.nodes[self.len - 1] = combine_states::<B>(self.nodes[self.len - 1], self.nodes[self.len]);
+        }
+        combine_states::<B>(self.nodes[0], load_u128(len as u64 as u128 | (chunk_size as u64 as u128) << 64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use rand::Rng;
+
+    // Reference implementation, combining nodes level by level
+    fn tree_reference(input: &[u8], seed: i64, chunk_size: usize) -> u128 {
+        dispatch!(B => unsafe {
+            let seed = tree_seed::<B>(seed);
+            let mut level: Vec<State> = leaves(input, chunk_size).enumerate().map(|(i, leaf)| hash_leaf::<B>(leaf, seed, i)).collect();
+            while level.len() > 1 {
+                level = level.chunks(2).map(|pair| if pair.len() == 2 { combine_states::<B>(pair[0], pair[1]) } else { pair[0] }).collect();
+            }
+            to_u128(combine_states::<B>(level[0], load_u128(input.len() as u128 | (chunk_size as u128) << 64)))
+        })
+    }
+
+    #[test]
+    fn tree_does_not_depend_on_threads() {
+        let mut bytes = vec![0u8; 10_000];
+        rand::thread_rng().fill(bytes.as_mut_slice());
+
+        for len in [0, 1, 100, 101, 999, 1000, 1001, 3000, 10_000] {
+            let input = &bytes[..len];
+            let expected = tree_reference(input, 42, 100);
+            assert_eq!(expected, gxhash_tree128_sequential(input, 42, 100), "Hash mismatch for input of size {len}");
+            for threads in 1..=9 {
+                assert_eq!(expected, gxhash_tree128_with_threads(input, 42, 100, threads), "Hash mismatch for input of size {len} on {threads} threads");
+            }
+            assert_eq!(expected, gxhash_tree128(input, 42, 100));
+        }
+    }
+
+    #[test]
+    fn tree_leaves_are_position_dependent() {
+        let mut bytes = vec![0u8; 200];
+        bytes[..100].fill(1);
+        let hash = gxhash_tree128(&bytes, 0, 100);
+        bytes.reverse();
+        assert_ne!(hash, gxhash_tree128(&bytes, 0, 100));
+        assert_ne!(hash, gxhash_tree128(&bytes, 0, 50));
+    }
+
+    #[test]
+    fn tree_is_stable() {
+        assert_eq!(286029100662515412785142091663186583791, gxhash_tree128(&[0u8; 0], 0, 1024));
+        assert_eq!(230096949670937130598454667821548859704, gxhash_tree128(&[42u8; 10_000], 0, 1024));
+        assert_eq!(303052151374282990718684368550466610169, gxhash_tree128(&[42u8; 10_000], 42, 10_000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tree_rejects_empty_chunks() {
+        gxhash_tree128(&[0u8; 10], 0, 0);
+    }
+}
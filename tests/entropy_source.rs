@@ -0,0 +1,29 @@
+//! Checks that `GxBuildHasher::default()` draws its seeds from the source set with `set_entropy_source`.
+//! The source is global to the process, so this test has its own binary to not affect other tests.
+
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use gxhash::*;
+
+static CALLS: AtomicU64 = AtomicU64::new(0);
+
+fn counting_source() -> u128 {
+    CALLS.fetch_add(1, Ordering::Relaxed) as u128 + 1
+}
+
+#[test]
+fn default_gxhasherbuilder_uses_entropy_source() {
+    if std::env::var_os("GXHASH_SEED").is_some() {
+        eprintln!("skipped: GXHASH_SEED overrides the entropy source");
+        return;
+    }
+
+    set_entropy_source(counting_source);
+    let a = GxBuildHasher::default();
+    let b = GxBuildHasher::default();
+    assert_eq!(2, CALLS.load(Ordering::Relaxed));
+    assert_eq!(1, a.seed());
+    assert_eq!(2, b.seed());
+    assert_ne!(a.hash_one(42), b.hash_one(42));
+}
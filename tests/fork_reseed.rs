@@ -0,0 +1,44 @@
+//! Checks that a forked child process doesn't continue the sequence of seeds of its parent.
+//! Forking a multithreaded process only leaves async-signal-safe calls to the child, so this test has its own
+//! binary without the test harness: the process is single-threaded when it forks.
+
+#[cfg(target_os = "linux")]
+fn main() {
+    use gxhash::GxBuildHasher;
+
+    if std::env::var_os("GXHASH_SEED").is_some() {
+        eprintln!("skipped: GXHASH_SEED overrides random seeds");
+        return;
+    }
+
+    // Registers the fork handler
+    GxBuildHasher::default();
+
+    let mut fds = [0; 2];
+    assert_eq!(0, unsafe { libc::pipe(fds.as_mut_ptr()) });
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0);
+    if pid == 0 {
+        // Without re-seeding, the child would continue the sequence of seeds of the parent
+        let seed = GxBuildHasher::default().seed().to_le_bytes();
+        let written = unsafe { libc::write(fds[1], seed.as_ptr().cast(), seed.len()) };
+        unsafe { libc::_exit(if written == seed.len() as isize { 0 } else { 1 }) };
+    }
+
+    let parent_seed = GxBuildHasher::default().seed();
+    let mut child_seed = [0u8; 16];
+    let read = unsafe { libc::read(fds[0], child_seed.as_mut_ptr().cast(), child_seed.len()) };
+    let mut status = 0;
+    unsafe {
+        libc::waitpid(pid, &mut status, 0);
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+    assert_eq!(16, read);
+    assert_ne!(parent_seed, u128::from_le_bytes(child_seed));
+    println!("fork_reseed: ok");
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {}
@@ -0,0 +1,154 @@
+//! Checks that hashing never touches memory outside of the input, by placing inputs right before or right after
+//! an inaccessible guard page: any read beyond the input on the wrong page faults and aborts the test.
+
+#![cfg(target_os = "linux")]
+
+use std::hash::{BuildHasher, Hasher};
+
+use gxhash::*;
+use rand::Rng;
+
+const MAX_LEN: usize = 256;
+
+/// Readable and writable pages surrounded by two `PROT_NONE` guard pages.
+struct GuardedPages {
+    base: *mut u8,
+    page_size: usize,
+    len: usize,
+}
+
+impl GuardedPages {
+    fn new(pages: usize) -> GuardedPages {
+        unsafe {
+            let page_size = libc::sysconf(libc::_SC_PAGESIZE) as usize;
+            let total = (pages + 2) * page_size;
+            let base = libc::mmap(
+                std::ptr::null_mut(),
+                total,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            assert_ne!(libc::MAP_FAILED, base, "mmap failed");
+            let base = base as *mut u8;
+            assert_eq!(0, libc::mprotect(base as *mut libc::c_void, page_size, libc::PROT_NONE));
+            assert_eq!(0, libc::mprotect(base.add(total - page_size) as *mut libc::c_void, page_size, libc::PROT_NONE));
+            GuardedPages { base, page_size, len: pages * page_size }
+        }
+    }
+
+    /// The accessible memory, which starts and ends on a page boundary.
+    fn bytes(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.base.add(self.page_size), self.len) }
+    }
+}
+
+impl Drop for GuardedPages {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.base as *mut libc::c_void, self.len + 2 * self.page_size);
+        }
+    }
+}
+
+/// Hashes through every public entry point reading from `input`.
+fn hashes(input: &[u8]) -> Vec<u128> {
+    let mut hashes = vec![
+        gxhash32(input, 42) as u128,
+        gxhash64(input, 42) as u128,
+        gxhash128(input, 42),
+    ];
+
+    let mut hasher = GxHasher::with_seed(42);
+    hasher.write(input);
+    hashes.push(hasher.finish_u128());
+
+    // A whole group of the batches, rather than the remainder which is hashed one input at a time
+    let build_hasher = GxBuildHasher::with_seed(42);
+    let mut batch = [0u64; 8];
+    gxhash64_batch(&[input; 8], 42, &mut batch);
+    hashes.extend(batch.iter().map(|&hash| hash as u128));
+    build_hasher.hash_batch(&[input; 8], &mut batch);
+    hashes.extend(batch.iter().map(|&hash| hash as u128));
+
+    // Integers read from the first and last bytes of the input, when it is long enough
+    macro_rules! write_int {
+        ($write:ident, $t:ty) => {
+            let size = std::mem::size_of::<$t>();
+            if input.len() >= size {
+                for bytes in [&input[..size], &input[input.len() - size..]] {
+                    let mut hasher = build_hasher.build_hasher();
+                    hasher.$write(<$t>::from_le_bytes(bytes.try_into().unwrap()));
+                    hashes.push(hasher.finish() as u128);
+                }
+            }
+        };
+    }
+    write_int!(write_u8, u8);
+    write_int!(write_u16, u16);
+    write_int!(write_u32, u32);
+    write_int!(write_u64, u64);
+    write_int!(write_u128, u128);
+
+    hashes
+}
+
+#[test]
+fn inputs_ending_on_guard_page() {
+    let mut pages = GuardedPages::new(1);
+    let mut rng = rand::thread_rng();
+
+    for len in 0..=MAX_LEN {
+        rng.fill(pages.bytes());
+        let end = pages.bytes().len();
+        // Same bytes at another address, away from guard pages
+        let copy = pages.bytes()[end - len..].to_vec();
+        let expected = hashes(&copy);
+
+        assert_eq!(expected, hashes(&pages.bytes()[end - len..]), "input of size {len} ending on page boundary");
+
+        // Bytes before the input must not change the hashes
+        rng.fill(&mut pages.bytes()[..end - len]);
+        assert_eq!(expected, hashes(&pages.bytes()[end - len..]), "input of size {len} depends on preceding bytes");
+    }
+}
+
+#[test]
+fn inputs_starting_on_guard_page() {
+    let mut pages = GuardedPages::new(1);
+    let mut rng = rand::thread_rng();
+
+    for len in 0..=MAX_LEN {
+        rng.fill(pages.bytes());
+        let copy = pages.bytes()[..len].to_vec();
+        let expected = hashes(&copy);
+
+        assert_eq!(expected, hashes(&pages.bytes()[..len]), "input of size {len} starting on page boundary");
+
+        // Bytes after the input must not change the hashes
+        rng.fill(&mut pages.bytes()[len..]);
+        assert_eq!(expected, hashes(&pages.bytes()[..len]), "input of size {len} depends on following bytes");
+    }
+}
+
+#[test]
+fn inputs_ending_near_guard_page() {
+    // Every misalignment of the input end relative to the page boundary, within a vector width
+    let mut pages = GuardedPages::new(1);
+    let mut rng = rand::thread_rng();
+
+    for gap in 0..32 {
+        for len in 0..=MAX_LEN {
+            rng.fill(pages.bytes());
+            let end = pages.bytes().len() - gap;
+            let start = end - len;
+            let copy = pages.bytes()[start..end].to_vec();
+            let expected = hashes(&copy);
+
+            rng.fill(&mut pages.bytes()[..start]);
+            rng.fill(&mut pages.bytes()[end..]);
+            assert_eq!(expected, hashes(&pages.bytes()[start..end]), "input of size {len} ending {gap} bytes before page boundary");
+        }
+    }
+}